@@ -1,17 +1,68 @@
 
-use crate::excel_table::ExcelTable;
+use crate::excel_table::{CellType, ExcelTable};
 use crate::vnxml;
 
 use zip::ZipArchive;
+use zip::result::ZipError;
 
+use xml::reader::{EventReader, XmlEvent};
+use xml::attribute::OwnedAttribute;
+
+use std::collections::HashMap;
 use std::path::Path;
-use std::io::{Read, Seek};
+use std::io::{BufReader, Read, Seek};
 use std::fs::File;
 use std::rc::Rc;
 
 pub struct ExcelFile<R: Read + Seek> {
     zip: ZipArchive<R>,
     strings: Option<Vec<Rc<String>>>,
+    styles: Option<Vec<NumberFormat>>,
+    date1904: bool,
+}
+
+/// the number format of a style as far as the cells are concerned
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum NumberFormat {
+    Other,
+    Date,
+    DateTime,
+}
+
+impl NumberFormat {
+    /// the built-in formats aren't written in styles.xml, 27-36 and 50-58 are the East Asian ones
+    fn builtin(id: u32) -> NumberFormat {
+        match id {
+            14..=17 | 27..=31 | 36 | 50..=54 | 57 | 58 => NumberFormat::Date,
+            22 => NumberFormat::DateTime,
+            _ => NumberFormat::Other,
+        }
+    }
+
+    /// a format with days or years is a date, with hours or seconds too it's a date time, quoted
+    /// texts, escaped characters and `[...]` like colors and locales don't count
+    fn from_code(code: &str) -> NumberFormat {
+        let mut date = false;
+        let mut time = false;
+        let mut chars = code.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => { chars.by_ref().find(|&t| t == '"'); }
+                '[' => { chars.by_ref().find(|&t| t == ']'); }
+                '\\' | '_' | '*' => { chars.next(); }
+                // only the section of the positive numbers
+                ';' => break,
+                'y' | 'Y' | 'd' | 'D' => date = true,
+                'h' | 'H' | 's' | 'S' => time = true,
+                _ => {}
+            }
+        }
+        match (date, time) {
+            (true, false) => NumberFormat::Date,
+            (true, true) => NumberFormat::DateTime,
+            _ => NumberFormat::Other,
+        }
+    }
 }
 
 impl<R: Read + Seek> ExcelFile<R> {
@@ -19,15 +70,27 @@ impl<R: Read + Seek> ExcelFile<R> {
         Ok(ExcelFile {
             zip: ZipArchive::new(s).map_err(|e| e.to_string())?,
             strings: None,
+            styles: None,
+            date1904: false,
         })
     }
 
-    pub fn parse_workbook(&mut self) -> Result<Vec<(String, u32)>, String> {
+    /// names of the sheets with the paths of their parts in the zip
+    pub fn parse_workbook(&mut self) -> Result<Vec<(String, String)>, String> {
+        let relationships = self.parse_relationships()?;
+
         let root =
         vnxml::read_from(
             self.zip.by_name("xl/workbook.xml").map_err(|e| e.to_string())?)
             .map_err(|e| e.to_string())?.ok_or_else(|| "parse workbook error".to_owned())?;
 
+        // the serial numbers of the dates count from 1904-01-01 instead of 1899-12-30
+        self.date1904 = root.children.iter()
+            .filter_map(|t| t.downcast_ref().to_element())
+            .find(|t| t.name.local_name == "workbookPr")
+            .and_then(|t| t.get_attribute(|t| t.local_name == "date1904"))
+            .map_or(false, |t| t == "1" || t == "true");
+
         let sheets = root.children.iter()
             .filter_map(|t| t.downcast_ref().to_element())
             .find(|t| t.name.local_name == "sheets").ok_or_else(|| "parse workbook error".to_owned())?;
@@ -41,9 +104,17 @@ impl<R: Read + Seek> ExcelFile<R> {
                 if let Some(attr_id) = sheet.get_attribute(
                     |t| t.prefix_ref()
                         .map_or(false, |t| t == "r") && t.local_name == "id") {
-                    if attr_id.starts_with("rId") {
+                    if let Some(relationships) = relationships.as_ref() {
+                        match relationships.get(attr_id) {
+                            Some((true, path)) => ret.push((attr_name.to_owned(), path.clone())),
+                            // chart sheets have no cells
+                            Some((false, _)) => {}
+                            None => return Err(format!("the relationship {} of sheet {} does not exist", attr_id, attr_name)),
+                        }
+                    } else if attr_id.starts_with("rId") {
+                        // workbooks without relationships name the sheets after their ids
                         if let Ok(sheet_id) = unsafe { attr_id.get_unchecked(3..).parse::<u32>() } {
-                            ret.push((attr_name.to_owned(), sheet_id));
+                            ret.push((attr_name.to_owned(), format!("xl/worksheets/sheet{}.xml", sheet_id)));
                         }
                     }
                 }
@@ -53,154 +124,264 @@ impl<R: Read + Seek> ExcelFile<R> {
         Ok(ret)
     }
 
-    pub fn parse_sheet(&mut self, sheet_id: u32) -> Result<ExcelTable, String> {
+    /// targets of the relationships of the workbook by their ids, with whether they're worksheets
+    fn parse_relationships(&mut self) -> Result<Option<HashMap<String, (bool, String)>>, String> {
+        let file = match self.zip.by_name("xl/_rels/workbook.xml.rels") {
+            Ok(t) => t,
+            Err(ZipError::FileNotFound) => return Ok(None),
+            Err(e) => return Err(e.to_string()),
+        };
+        let root = vnxml::read_from(file)
+            .map_err(|e| e.to_string())?.ok_or_else(|| "parse workbook relationships error".to_owned())?;
+
+        let mut ret = HashMap::new();
+        for relationship in root.children.iter()
+            .filter_map(|t| t.downcast_ref().to_element())
+            .filter(|t| t.name.local_name == "Relationship") {
+            if relationship.get_attribute(|t| t.local_name == "TargetMode")
+                .map_or(false, |t| t == "External") {
+                continue;
+            }
+            if let (Some(id), Some(ty), Some(target)) = (
+                relationship.get_attribute(|t| t.local_name == "Id"),
+                relationship.get_attribute(|t| t.local_name == "Type"),
+                relationship.get_attribute(|t| t.local_name == "Target")) {
+                ret.insert(id.clone(), (ty.ends_with("/worksheet"), resolve_target(target)));
+            }
+        }
+
+        Ok(Some(ret))
+    }
+
+    pub fn parse_sheet(&mut self, path: &str) -> Result<ExcelTable, String> {
         let strings;
         if let Some(t) = self.strings.take() {
             strings = t;
         } else {
             strings = self.parse_shared_strings()?;
         }
+        let styles;
+        if let Some(t) = self.styles.take() {
+            styles = t;
+        } else {
+            styles = self.parse_styles()?;
+        }
 
-        let ret = self._parse_sheet(&strings, sheet_id);
+        let ret = self._parse_sheet(&strings, &styles, path);
 
         self.strings = Some(strings);
+        self.styles = Some(styles);
 
         ret
     }
 
-    fn parse_shared_strings(&mut self) -> Result<Vec<Rc<String>>, String> {
-        let root =
-        vnxml::read_from(
-            self.zip.by_name("xl/sharedStrings.xml").map_err(|e| e.to_string())?)
-            .map_err(|e| e.to_string())?.ok_or_else(|| "parse shared strings error".to_owned())?;
-
-        let mut strings =
-            if let Some(t) = root
-                .parse_attribute(|t| t.local_name == "uniqueCount") {
-                Vec::with_capacity(t)
-            } else {
-                Vec::new()
-            };
-
-        for si in root.children.iter()
-            .filter_map(|t| t.downcast_ref().to_element())
-            .filter(|t|t.name.local_name == "si") {
-            if let Some(t) = si.children.iter()
-                .filter_map(|t|t.downcast_ref().to_element())
-                .find(|&t|t.name.local_name == "t") {
-                if let Some(text) = t.children.iter()
-                    .find_map(|t|t.downcast_ref().to_text()) {
-                    strings.push(Rc::new(text.content.clone()));
-                } else {
-                    strings.push(Rc::default());
-                }
-            } else {
-                let mut content = String::new();
-                si.children.iter()
-                    .filter_map(|t| t.downcast_ref().to_element())
-                    .filter(|&t| t.name.local_name == "r")
-                    .flat_map(|t| t.children.iter())
-                    .filter_map(|t| t.downcast_ref().to_element())
-                    .filter(|&t| t.name.local_name == "t")
-                    .for_each(|t| {
-                        if let Some(text) = t.children.iter()
-                            .find_map(|t| t.downcast_ref().to_text()) {
-                            content.push_str(text.content.as_str());
+    /// number formats of the cell styles, `s` of a cell is the index
+    fn parse_styles(&mut self) -> Result<Vec<NumberFormat>, String> {
+        let file = match self.zip.by_name("xl/styles.xml") {
+            Ok(t) => t,
+            Err(ZipError::FileNotFound) => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+        let reader = xml_reader(file);
+
+        let mut formats = HashMap::new();
+        let mut styles = Vec::new();
+        // `<xf>` of `<cellStyleXfs>` are the named styles which cells don't refer to
+        let mut in_cell_xfs = false;
+
+        for ev in reader {
+            match ev.map_err(|e| e.to_string())? {
+                XmlEvent::StartElement { name, attributes, .. } => {
+                    match name.local_name.as_str() {
+                        "numFmt" => {
+                            if let (Some(id), Some(code)) = (
+                                get_attribute(&attributes, "numFmtId").and_then(|t| t.parse::<u32>().ok()),
+                                get_attribute(&attributes, "formatCode")) {
+                                formats.insert(id, NumberFormat::from_code(code));
+                            }
                         }
-                    });
-                strings.push(Rc::new(content));
+                        "cellXfs" => in_cell_xfs = true,
+                        "xf" if in_cell_xfs => {
+                            let id = get_attribute(&attributes, "numFmtId")
+                                .and_then(|t| t.parse::<u32>().ok())
+                                .unwrap_or(0);
+                            styles.push(formats.get(&id).copied().unwrap_or_else(|| NumberFormat::builtin(id)));
+                        }
+                        _ => {}
+                    }
+                }
+                XmlEvent::EndElement { name } => {
+                    if name.local_name == "cellXfs" {
+                        in_cell_xfs = false;
+                    }
+                }
+                _ => {}
             }
         }
 
-        Ok(strings)
+        Ok(styles)
     }
 
-    fn _parse_sheet(&mut self, strings: &Vec<Rc<String>>, sheet_id: u32) -> Result<ExcelTable, String> {
-
-        let root =
-            vnxml::read_from(
-                self.zip.by_name(format!("xl/worksheets/sheet{}.xml", sheet_id).as_str())
-                    .map_err(|_| format!("sheet{} does not exist", sheet_id))?).map_err(|e| e.to_string())?;
-
-
-        let parse = || {
-            let root = root?;
-            let (origin, size) = parse_dimension(
-                root.children.iter().filter_map(|t| t.downcast_ref().to_element())
-                    .find(|&t| t.name.local_name == "dimension")?.get_attribute(|t| t.local_name == "ref")?)?;
-
-            let count = size.0 * size.1;
-            let mut cells = Vec::with_capacity(count);
-            cells.extend(std::iter::repeat(None).take(count));
-
-            let sheet_data = root.children.iter().filter_map(|t| t.downcast_ref().to_element())
-                .find(|&t| t.name.local_name == "sheetData")?;
-
-            for row in sheet_data.children.iter().filter_map(|t| t.downcast_ref().to_element())
-                .filter(|&t| t.name.local_name == "row") {
-                for cell in row.children.iter().filter_map(|t| t.downcast_ref().to_element())
-                    .filter(|&t| t.name.local_name == "c") {
-                    let (mut x, mut y) = parse_position(cell.get_attribute(|t| t.local_name == "r")?)?;
-                    x = x.wrapping_sub(origin.0);
-                    y = y.wrapping_sub(origin.1);
-                    if x > size.0 || y > size.1 {
-                        continue;
-                    }
-
-                    let val = cell.children.iter().filter_map(|t| t.downcast_ref().to_element())
-                        .find(|&t| t.name.local_name == "v");
-
-                    let val = if let Some(t) = val {
-                        t.children.iter().filter_map(|t| t.downcast_ref().to_text()).next()
-                    } else {
-                        continue;
-                    };
-
-                    if let Some(text) = val {
-                        unsafe {
-                            *cells.get_unchecked_mut(y * size.0 + x) =
-                                if let Some(idx) = cell.get_attribute(|t| t.local_name == "t")
-                                    .and_then(|t| if t == "s" { Some(()) } else { None })
-                                    .and_then(|_| text.content.parse::<usize>().ok())
-                                    .filter(|&t| t < strings.len()) {
-                                    Some(strings.get_unchecked(idx).clone())
-                                } else {
-                                    Some(Rc::new(text.content.clone()))
-                                };
+    fn parse_shared_strings(&mut self) -> Result<Vec<Rc<String>>, String> {
+        // workbooks without any text have no shared strings
+        let file = match self.zip.by_name("xl/sharedStrings.xml") {
+            Ok(t) => t,
+            Err(ZipError::FileNotFound) => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+        let reader = xml_reader(file);
+
+        let mut strings = Vec::new();
+        let mut content = String::new();
+        // the texts of `<t>` in `<si>` or in its rich text runs `<r>`, phonetic runs are skipped
+        let mut in_t = false;
+        let mut in_phonetic = false;
+
+        for ev in reader {
+            match ev.map_err(|e| e.to_string())? {
+                XmlEvent::StartElement { name, attributes, .. } => {
+                    match name.local_name.as_str() {
+                        "sst" => {
+                            if let Some(t) = get_attribute(&attributes, "uniqueCount")
+                                .and_then(|t| t.parse().ok()) {
+                                strings.reserve(t);
+                            }
                         }
+                        "si" => content.clear(),
+                        "rPh" => in_phonetic = true,
+                        "t" => in_t = !in_phonetic,
+                        _ => {}
+                    }
+                }
+                XmlEvent::EndElement { name } => {
+                    match name.local_name.as_str() {
+                        "si" => strings.push(Rc::new(std::mem::take(&mut content))),
+                        "rPh" => in_phonetic = false,
+                        "t" => in_t = false,
+                        _ => {}
+                    }
+                }
+                XmlEvent::Characters(text) => {
+                    if in_t {
+                        content.push_str(text.as_str());
                     }
                 }
+                _ => {}
             }
+        }
 
-            let mut merged_cells;
+        Ok(strings)
+    }
 
-            if let Some(element) = root.children.iter().filter_map(|t| t.downcast_ref().to_element())
-            .find(|&t| t.name.local_name == "mergeCells") {
-                if let Some(count) = element.parse_attribute(|t| t.local_name == "count") {
-                    merged_cells = Vec::with_capacity(count);
-                } else {
-                    merged_cells = Vec::new();
+    fn _parse_sheet(&mut self, strings: &Vec<Rc<String>>, styles: &Vec<NumberFormat>, path: &str) -> Result<ExcelTable, String> {
+        let reader = xml_reader(
+            self.zip.by_name(path)
+                .map_err(|_| format!("{} does not exist", path))?);
+
+        let date1904 = self.date1904;
+
+        // the events are consumed as they come, cells are written into the table directly
+        let mut dimension = None;
+        let mut cells = Vec::new();
+        let mut types = Vec::new();
+        let mut dates = HashMap::new();
+        let mut merged_cells = Vec::new();
+
+        // (index in cells, `t`, number format) of the current `<c>`, none if it's out of the dimension
+        let mut cell = None;
+        // the text of `<v>` or of the `<t>` of an inline string `<is>`
+        let mut in_v = false;
+        let mut in_is = false;
+        let mut in_t = false;
+        let mut in_phonetic = false;
+        let mut has_text = false;
+        let mut text = String::new();
+
+        for ev in reader {
+            match ev.map_err(|e| e.to_string())? {
+                XmlEvent::StartElement { name, attributes, .. } => {
+                    match name.local_name.as_str() {
+                        "dimension" => {
+                            let (origin, size) = get_attribute(&attributes, "ref")
+                                .and_then(parse_dimension)
+                                .ok_or_else(|| "parse sheet error".to_owned())?;
+                            let count = size.0 * size.1;
+                            cells = Vec::with_capacity(count);
+                            cells.extend(std::iter::repeat(None).take(count));
+                            types = vec![CellType::String; count];
+                            dimension = Some((origin, size));
+                        }
+                        "c" => {
+                            let (origin, size) = dimension.ok_or_else(|| "parse sheet error".to_owned())?;
+                            let (mut x, mut y) = get_attribute(&attributes, "r")
+                                .and_then(parse_position)
+                                .ok_or_else(|| "parse sheet error".to_owned())?;
+                            x = x.wrapping_sub(origin.0);
+                            y = y.wrapping_sub(origin.1);
+                            cell = if x < size.0 && y < size.1 {
+                                let format = get_attribute(&attributes, "s")
+                                    .and_then(|t| t.parse::<usize>().ok())
+                                    .and_then(|t| styles.get(t).copied())
+                                    .unwrap_or(NumberFormat::Other);
+                                Some((y * size.0 + x, get_attribute(&attributes, "t").unwrap_or("n").to_owned(), format))
+                            } else {
+                                None
+                            };
+                            has_text = false;
+                            text.clear();
+                        }
+                        "v" => in_v = true,
+                        "is" => in_is = true,
+                        "rPh" => in_phonetic = true,
+                        "t" => in_t = in_is && !in_phonetic,
+                        "mergeCells" => {
+                            if let Some(count) = get_attribute(&attributes, "count")
+                                .and_then(|t| t.parse().ok()) {
+                                merged_cells.reserve(count);
+                            }
+                        }
+                        "mergeCell" => {
+                            if let (Some((origin, _)), Some(t)) = (dimension, get_attribute(&attributes, "ref")
+                                .and_then(parse_dimension)) {
+                                merged_cells.push((((t.0).0 - origin.0, (t.0).1 - origin.1), t.1));
+                            }
+                        }
+                        _ => {}
+                    }
                 }
-
-                for child in element.children.iter().filter_map(|t| t.downcast_ref().to_element())
-                .filter(|&t| t.name.local_name == "mergeCell") {
-                    if let Some(text) = child.get_attribute(|t| t.local_name == "ref") {
-                        if let Some(t) = parse_dimension(text) {
-                            merged_cells.push((((t.0).0 - origin.0, (t.0).1 - origin.1), t.1));
+                XmlEvent::EndElement { name } => {
+                    match name.local_name.as_str() {
+                        "c" => {
+                            if let Some((idx, ty, format)) = cell.take().filter(|_| has_text) {
+                                let (ty, value, date) = cell_value(strings, &ty, format, &text, date1904);
+                                types[idx] = ty;
+                                cells[idx] = Some(value);
+                                if let Some(date) = date {
+                                    dates.insert(idx, date);
+                                }
+                            }
                         }
+                        "v" => in_v = false,
+                        "is" => in_is = false,
+                        "rPh" => in_phonetic = false,
+                        "t" => in_t = false,
+                        _ => {}
                     }
                 }
-
-                merged_cells.sort_unstable_by(|a, b| a.0.cmp(&b.0));
-
-            } else {
-                merged_cells = Vec::new();
+                XmlEvent::Characters(t) => {
+                    if cell.is_some() && (in_v || in_t) {
+                        text.push_str(t.as_str());
+                        has_text = true;
+                    }
+                }
+                _ => {}
             }
+        }
 
-            Some(ExcelTable::new(origin, size, cells, merged_cells))
-        };
+        let (origin, size) = dimension.ok_or_else(|| "parse sheet error".to_owned())?;
+        merged_cells.sort_unstable_by(|a, b| a.0.cmp(&b.0));
 
-        parse().ok_or_else(|| "parse sheet error".to_owned())
+        Ok(ExcelTable::new(origin, size, cells, types, dates, merged_cells))
     }
 }
 
@@ -210,6 +391,89 @@ impl ExcelFile<File> {
     }
 }
 
+fn xml_reader<R: Read>(s: R) -> EventReader<BufReader<R>> {
+    xml::reader::ParserConfig::new()
+        .whitespace_to_characters(true)
+        .cdata_to_characters(true)
+        .create_reader(BufReader::new(s))
+}
+
+/// type and text of a cell by its `t`, with the `yyyy-mm-dd` form of the dates
+fn cell_value(strings: &Vec<Rc<String>>, ty: &str, format: NumberFormat, text: &str, date1904: bool) -> (CellType, Rc<String>, Option<String>) {
+    match ty {
+        "s" => {
+            if let Some(t) = text.parse::<usize>().ok().and_then(|t| strings.get(t)) {
+                (CellType::String, t.clone(), None)
+            } else {
+                (CellType::String, Rc::new(text.to_owned()), None)
+            }
+        }
+        "str" | "inlineStr" => (CellType::String, Rc::new(text.to_owned()), None),
+        "b" => (CellType::Bool, Rc::new(text.to_owned()), None),
+        "e" => (CellType::Error, Rc::new(text.to_owned()), None),
+        // ISO 8601 already
+        "d" => (CellType::Date, Rc::new(text.to_owned()), Some(text.to_owned())),
+        _ if format != NumberFormat::Other => {
+            let date = text.parse::<f64>().ok()
+                .and_then(|t| format_date(t, date1904, format == NumberFormat::DateTime));
+            (CellType::Date, Rc::new(text.to_owned()), date)
+        }
+        _ => (CellType::Number, Rc::new(text.to_owned()), None),
+    }
+}
+
+/// `yyyy-mm-dd` or `yyyy-mm-dd hh:mm:ss` of the serial number of a date
+fn format_date(serial: f64, date1904: bool, time: bool) -> Option<String> {
+    if !serial.is_finite() || serial < 0.0 || serial > 2958465.0 {
+        return None;
+    }
+    let secs = (serial * 86400.0).round() as i64;
+    // days since 1970-01-01, the 1900 system counts from 1899-12-30 to skip its fake 1900-02-29
+    let days = secs / 86400 - if date1904 { 24107 } else { 25569 };
+    let secs = secs % 86400;
+
+    // civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+
+    if time {
+        Some(format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", y, m, d, secs / 3600, secs % 3600 / 60, secs % 60))
+    } else {
+        Some(format!("{:04}-{:02}-{:02}", y, m, d))
+    }
+}
+
+// the targets are relative to `xl/` where workbook.xml is, or absolute in the package
+fn resolve_target(target: &str) -> String {
+    let path = if let Some(t) = target.strip_prefix('/') {
+        t.to_owned()
+    } else {
+        format!("xl/{}", target)
+    };
+    let mut parts = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => { parts.pop(); }
+            t => parts.push(t),
+        }
+    }
+    parts.join("/")
+}
+
+fn get_attribute<'a>(attributes: &'a [OwnedAttribute], local_name: &str) -> Option<&'a str> {
+    attributes.iter()
+        .find(|&t| t.name.local_name == local_name)
+        .map(|t| t.value.as_str())
+}
+
 fn parse_dimension(dimension: &str) -> Option<((usize, usize), (usize, usize))> {
     if let Some(idx) =
         dimension.bytes().enumerate().find(|t| t.1 == b':')
@@ -254,4 +518,177 @@ fn parse_position(position: &str) -> Option<(usize, usize)> {
     }
 
     Some((x, y))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+fn zip_of(files: &[(&str, &str)]) -> std::io::Cursor<Vec<u8>> {
+    use std::io::Write;
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (name, content) in files {
+        zip.start_file(*name, zip::write::FileOptions::default()).unwrap();
+        zip.write_all(content.as_bytes()).unwrap();
+    }
+    let mut ret = zip.finish().unwrap();
+    ret.set_position(0);
+    ret
+}
+
+#[test]
+fn test_resolve_target() {
+    assert_eq!(resolve_target("worksheets/sheet1.xml"), "xl/worksheets/sheet1.xml");
+    assert_eq!(resolve_target("./worksheets/sheet1.xml"), "xl/worksheets/sheet1.xml");
+    assert_eq!(resolve_target("../sheets/sheet1.xml"), "sheets/sheet1.xml");
+    assert_eq!(resolve_target("worksheets/../worksheets/sheet1.xml"), "xl/worksheets/sheet1.xml");
+    assert_eq!(resolve_target("/xl/worksheets/sheet1.xml"), "xl/worksheets/sheet1.xml");
+}
+
+#[test]
+fn test_parse_workbook() {
+    let workbook = r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+        <sheets>
+            <sheet name="Item" sheetId="1" r:id="rId3"/>
+            <sheet name="Chart" sheetId="2" r:id="rId2"/>
+            <sheet name="Skill" sheetId="3" r:id="rId1"/>
+            <sheet name="Buff" sheetId="4" r:id="rId4"/>
+        </sheets>
+    </workbook>"#;
+    // the targets have nothing to do with the sheetIds
+    let rels = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+        <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet7.xml"/>
+        <Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/chartsheet" Target="chartsheets/sheet1.xml"/>
+        <Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="/xl/worksheets/sheet2.xml"/>
+        <Relationship Id="rId4" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="../sheets/buff.xml"/>
+        <Relationship Id="rId5" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="http://example.com" TargetMode="External"/>
+    </Relationships>"#;
+    let mut excel = ExcelFile::load_from(zip_of(&[
+        ("xl/workbook.xml", workbook),
+        ("xl/_rels/workbook.xml.rels", rels),
+    ])).unwrap();
+    assert_eq!(excel.parse_workbook().unwrap(), vec![
+        ("Item".to_owned(), "xl/worksheets/sheet2.xml".to_owned()),
+        ("Skill".to_owned(), "xl/worksheets/sheet7.xml".to_owned()),
+        ("Buff".to_owned(), "sheets/buff.xml".to_owned()),
+    ]);
+
+    // a relationship which doesn't exist
+    let mut excel = ExcelFile::load_from(zip_of(&[
+        ("xl/workbook.xml", workbook),
+        ("xl/_rels/workbook.xml.rels", &rels.replace("rId4", "rId6")),
+    ])).unwrap();
+    assert!(excel.parse_workbook().is_err());
+
+    // without relationships the sheets are named after their ids
+    let mut excel = ExcelFile::load_from(zip_of(&[("xl/workbook.xml", workbook)])).unwrap();
+    assert_eq!(excel.parse_workbook().unwrap()[0], ("Item".to_owned(), "xl/worksheets/sheet3.xml".to_owned()));
+}
+
+#[test]
+fn test_parse_sheet() {
+    use crate::excel_table::CellValue;
+    // no shared strings, styles nor relationships
+    let workbook = r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+        <sheets><sheet name="Template" sheetId="1" r:id="rId1"/></sheets>
+    </workbook>"#;
+    let sheet = r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+        <dimension ref="A1:C1"/>
+        <sheetData><row r="1">
+            <c r="A1" t="inlineStr"><is><t>Id</t></is></c>
+            <c r="B1" t="b"><v>1</v></c>
+            <c r="C1"><v>42</v></c>
+        </row></sheetData>
+    </worksheet>"#;
+    let mut excel = ExcelFile::load_from(zip_of(&[
+        ("xl/workbook.xml", workbook),
+        ("xl/worksheets/sheet1.xml", sheet),
+    ])).unwrap();
+    let sheets = excel.parse_workbook().unwrap();
+    let table = excel.parse_sheet(&sheets[0].1).unwrap();
+    assert_eq!(table.cell_value(0, 0), Some(CellValue::String("Id")));
+    assert_eq!(table.cell_value(1, 0), Some(CellValue::Bool(true)));
+    assert_eq!(table.cell_value(2, 0), Some(CellValue::Number("42")));
+}
+
+#[test]
+fn test_parse_sheet_streaming() {
+    use crate::excel_table::CellValue;
+    let workbook = r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+        <sheets><sheet name="Template" sheetId="1" r:id="rId1"/></sheets>
+    </workbook>"#;
+    // rich text runs are joined, the phonetic runs are skipped and the preserved spaces are kept
+    let shared = r#"<sst xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" count="3" uniqueCount="3">
+        <si><t>Id</t></si>
+        <si><r><rPr><b/></rPr><t>火</t></r><r><t xml:space="preserve"> 球</t></r><rPh sb="0" eb="1"><t>ひ</t></rPh></si>
+        <si><t xml:space="preserve">  </t></si>
+    </sst>"#;
+    let sheet = r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+        <dimension ref="B2:D3"/>
+        <sheetData>
+            <row r="1"><c r="A1"><v>1</v></c></row>
+            <row r="2">
+                <c r="B2" t="s"><v>0</v></c>
+                <c r="C2" t="s"><v>1</v></c>
+                <c r="D2" t="s"><v>2</v></c>
+                <c r="E2"><v>9</v></c>
+            </row>
+            <row r="3"><c r="B3" t="inlineStr"><is><t>a</t><rPh sb="0" eb="1"><t>b</t></rPh></is></c></row>
+        </sheetData>
+        <mergeCells count="2"><mergeCell ref="C3:D3"/><mergeCell ref="B2:B3"/></mergeCells>
+    </worksheet>"#;
+    let mut excel = ExcelFile::load_from(zip_of(&[
+        ("xl/workbook.xml", workbook),
+        ("xl/sharedStrings.xml", shared),
+        ("xl/worksheets/sheet1.xml", sheet),
+    ])).unwrap();
+    let sheets = excel.parse_workbook().unwrap();
+    let table = excel.parse_sheet(&sheets[0].1).unwrap();
+    assert_eq!(table.size(), &(3, 2));
+    assert_eq!(table.cell_value(0, 0), Some(CellValue::String("Id")));
+    assert_eq!(table.cell_value(1, 0), Some(CellValue::String("火 球")));
+    assert_eq!(table.cell_value(2, 0), Some(CellValue::String("  ")));
+    assert_eq!(table.cell_value(0, 1), Some(CellValue::String("a")));
+    // the cells out of the dimension are dropped
+    assert_eq!(table.cell_content(1, 1), None);
+    assert_eq!(table.cell_content(2, 1), None);
+    // the merged cells are sorted by their positions
+    assert_eq!(table.merged_cells(), &[((0, 0), (1, 2)), ((1, 1), (2, 1))]);
+    assert_eq!(table.get_merged_cell_size(1, 1), Some((2, 1)));
+    assert_eq!(table.get_merged_cell_size(2, 1), None);
+}
+
+#[test]
+fn test_format_date() {
+    // the 1900 system counts from 1899-12-30, 60 is the 1900-02-29 which never was
+    assert_eq!(format_date(45413.0, false, false).as_deref(), Some("2024-05-01"));
+    assert_eq!(format_date(61.0, false, false).as_deref(), Some("1900-03-01"));
+    // the 1904 system counts from 1904-01-01
+    assert_eq!(format_date(0.0, true, false).as_deref(), Some("1904-01-01"));
+    assert_eq!(format_date(43951.0, true, false).as_deref(), Some("2024-05-01"));
+    // the fraction is the time of the day
+    assert_eq!(format_date(45413.4166666667, false, true).as_deref(), Some("2024-05-01 10:00:00"));
+    assert_eq!(format_date(45413.75, false, true).as_deref(), Some("2024-05-01 18:00:00"));
+    assert_eq!(format_date(45413.75, false, false).as_deref(), Some("2024-05-01"));
+    assert_eq!(format_date(-1.0, false, false), None);
+    assert_eq!(format_date(f64::NAN, false, false), None);
+}
+
+#[test]
+fn test_number_format() {
+    assert_eq!(NumberFormat::builtin(0), NumberFormat::Other);
+    assert_eq!(NumberFormat::builtin(14), NumberFormat::Date);
+    assert_eq!(NumberFormat::builtin(22), NumberFormat::DateTime);
+    assert_eq!(NumberFormat::builtin(49), NumberFormat::Other);
+    assert_eq!(NumberFormat::builtin(58), NumberFormat::Date);
+
+    assert_eq!(NumberFormat::from_code("yyyy-mm-dd"), NumberFormat::Date);
+    assert_eq!(NumberFormat::from_code("yyyy/m/d h:mm:ss"), NumberFormat::DateTime);
+    assert_eq!(NumberFormat::from_code("0.00"), NumberFormat::Other);
+    // quoted texts, escaped characters and brackets
+    assert_eq!(NumberFormat::from_code(r#"0 "days""#), NumberFormat::Other);
+    assert_eq!(NumberFormat::from_code(r#"0\d"#), NumberFormat::Other);
+    assert_eq!(NumberFormat::from_code("[Red]0.00"), NumberFormat::Other);
+    assert_eq!(NumberFormat::from_code(r#"[$-804]yyyy"年"m"月"d"日""#), NumberFormat::Date);
+    // only the first section counts
+    assert_eq!(NumberFormat::from_code(r#"0;[Red]-0;"-""#), NumberFormat::Other);
+    assert_eq!(NumberFormat::from_code("0;yyyy-mm-dd"), NumberFormat::Other);
+    assert_eq!(NumberFormat::from_code("yyyy-mm-dd;@"), NumberFormat::Date);
+}
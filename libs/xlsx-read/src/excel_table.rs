@@ -1,14 +1,42 @@
 
 use std::rc::Rc;
 use std::fmt;
+use std::collections::HashMap;
 
 pub struct ExcelTable {
     pub origin: (usize, usize),
     pub size: (usize, usize),
     pub cells: Vec<Option<Rc<String>>>,
+    pub types: Vec<CellType>,
+    /// `yyyy-mm-dd` or `yyyy-mm-dd hh:mm:ss` of the date cells by their indices in `cells`
+    pub dates: HashMap<usize, String>,
     pub merged_cells: Vec<((usize, usize), (usize, usize))>,
 }
 
+/// type of a cell by its `t` attribute and the number format of its style
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CellType {
+    String,
+    Number,
+    Bool,
+    Error,
+    Date,
+}
+
+/// content of a cell by its type, the texts are the ones in `ExcelTable::cells`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CellValue<'a> {
+    /// shared, inline or formula string
+    String(&'a str),
+    /// the number as it's written in the sheet
+    Number(&'a str),
+    Bool(bool),
+    /// `#N/A`, `#DIV/0!`, `#REF!` ...
+    Error(&'a str),
+    /// the serial number of a date formatted number, see `ExcelTable::date_text` for the date
+    Date(&'a str),
+}
+
 pub struct RangeName<'a> (&'a ExcelTable, usize, usize, usize, usize);
 
 impl<'a> fmt::Display for RangeName<'a> {
@@ -42,8 +70,8 @@ impl<'a> fmt::Display for RangeName<'a> {
 
 impl ExcelTable {
 
-    pub(crate) fn new(origin: (usize, usize), size: (usize, usize), cells: Vec<Option<Rc<String>>>, merged_cells: Vec<((usize, usize), (usize, usize))>) -> ExcelTable {
-        ExcelTable { origin, size, cells, merged_cells }
+    pub(crate) fn new(origin: (usize, usize), size: (usize, usize), cells: Vec<Option<Rc<String>>>, types: Vec<CellType>, dates: HashMap<usize, String>, merged_cells: Vec<((usize, usize), (usize, usize))>) -> ExcelTable {
+        ExcelTable { origin, size, cells, types, dates, merged_cells }
     }
     
 
@@ -77,6 +105,30 @@ impl ExcelTable {
         self.cells.get_unchecked(y * self.size.0 + x).as_ref().map(|t| t.as_str())
     }
 
+    pub fn cell_type(&self, x: usize, y: usize) -> Option<CellType> {
+        self.cell(x, y)
+            .and_then(|_| self.types.get(y * self.size.0 + x).copied())
+    }
+
+    pub fn cell_value(&self, x: usize, y: usize) -> Option<CellValue<'_>> {
+        let text = self.cell_content(x, y)?;
+        Some(match self.types.get(y * self.size.0 + x)? {
+            CellType::String => CellValue::String(text),
+            CellType::Number => CellValue::Number(text),
+            CellType::Bool => CellValue::Bool(text != "0" && !text.eq_ignore_ascii_case("false")),
+            CellType::Error => CellValue::Error(text),
+            CellType::Date => CellValue::Date(text),
+        })
+    }
+
+    /// the date of a date cell, its text stays the serial number since the same style may be put
+    /// on the numbers which aren't dates
+    pub fn date_text(&self, x: usize, y: usize) -> Option<&str> {
+        self.cell(x, y)
+            .and_then(|_| self.dates.get(&(y * self.size.0 + x)))
+            .map(|t| t.as_str())
+    }
+
     pub fn get_column_name(&self, x: usize) -> String {
         let mut n = x + self.origin.0 - 1;
         let mut name = Vec::new();
@@ -1,25 +1,5 @@
 use clap::{Parser, Subcommand};
 
-pub enum LanguageOption {
-    CN,
-    CNH,
-    EN,
-    JP,
-    Invalid,
-}
-
-impl From<&str> for LanguageOption {
-    fn from(value: &str) -> Self {
-        match value {
-            "CN" | "cn" | "Cn" | "cN" => Self::CN,
-            "CNH" | "cnh" => Self::CNH,
-            "EN" | "en" => Self::EN,
-            "JP" | "jp" => Self::JP,
-            _ => Self::Invalid,
-        }
-    }
-}
-
 #[derive(Parser, Debug)]
 #[command(version, about)]
 pub struct Args {
@@ -47,7 +27,7 @@ pub struct Args {
     /// path of the ConfigCollection.cs
     #[arg(default_value_t = String::from(""), long)]
     pub config_collection_path: String,
-    /// language_option
+    /// language_option, `all` or a comma separated list of the `languages` in config.tnl
     #[arg(default_value_t = String::from("CN"), long)]
     pub loption: String,
     /// language file output dir
@@ -59,6 +39,9 @@ pub struct Args {
     /// only export LString.xlsx
     #[arg(default_value_t = false, short, long)]
     pub lstring: bool,
+    /// renumber the persisted lstring ids of the tables without gaps
+    #[arg(default_value_t = false, long)]
+    pub compact_lstrings: bool,
     /// excluded folders
     #[arg(default_value_t = String::default(), long)]
     pub excluded_folders: String,
@@ -73,4 +56,52 @@ pub enum Command {
 
     #[command(name = "clean", visible_alias = "c")]
     Clean,
+
+    /// export the fk dependency graph between tables
+    #[command(name = "graph", visible_alias = "g")]
+    Graph {
+        /// output format, `dot` or `mermaid`
+        #[arg(default_value_t = String::from("dot"), long)]
+        format: String,
+        /// output file, print to stdout if empty
+        #[arg(default_value_t = String::default(), short, long)]
+        output: String,
+    },
+
+    /// exchange the localizable texts with translators
+    #[command(name = "lang", visible_alias = "l")]
+    Lang {
+        #[command(subcommand)]
+        command: LangCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum LangCommand {
+    /// export the texts for the `--loption` languages, one file per language
+    #[command(name = "export")]
+    Export {
+        /// output format, `xliff` or `po`
+        #[arg(default_value_t = String::from("xliff"), long)]
+        format: String,
+        /// output dir
+        #[arg(default_value_t = String::from("Translations/"), short, long)]
+        output: String,
+    },
+
+    /// report the missing and stale translations of the LString sheets of every language
+    #[command(name = "report")]
+    Report {
+        /// output file, print to stdout if empty
+        #[arg(default_value_t = String::default(), short, long)]
+        output: String,
+    },
+
+    /// import translated files into the overlays used by the build
+    #[command(name = "import")]
+    Import {
+        /// `.po`, `.xlf` or `.xliff` files
+        #[arg(required = true)]
+        files: Vec<String>,
+    },
 }
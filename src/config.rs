@@ -1,7 +1,26 @@
 #![allow(dead_code)]
 
 use crate::error::Error;
-use std::{collections::HashSet, sync::LazyLock};
+use dashmap::DashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::LazyLock,
+};
+
+/// a named type shared by the workbooks, fk pattern and default are used when the cells are empty
+pub struct TypeAlias {
+    pub ty: &'static str,
+    pub fk: &'static str,
+    pub default: &'static str,
+}
+
+/// a language derived from `base` for testing the UI, see `markup::pseudo`
+pub struct PseudoLanguage {
+    pub name: &'static str,
+    pub base: &'static str,
+    pub expansion: f32,
+    pub markers: (&'static str, &'static str),
+}
 
 pub struct Config {
     pub row_of_start: usize,
@@ -28,6 +47,13 @@ pub struct Config {
     pub ref_start_num: i32,
     pub align_str: &'static str,
     pub ban_list: Vec<&'static str>,
+    pub custom_types_file: &'static str,
+    pub time_unit: &'static str,
+    pub type_aliases: HashMap<&'static str, TypeAlias>,
+    pub languages: Vec<&'static str>,
+    pub pseudo_language: Option<PseudoLanguage>,
+    /// the keys of every sheet of LString.xlsx are grouped in a nested class of LanguageKey
+    pub nested_language_keys: bool,
 }
 
 impl Config {
@@ -73,8 +99,166 @@ impl Config {
                 }
                 r
             },
+            custom_types_file: match config.optional_attribute("custom_types_file") {
+                Some(v) => v.as_str()?,
+                None => "",
+            },
+            time_unit: match config.optional_attribute("time_unit") {
+                Some(v) => match v.as_str()? {
+                    v @ ("s" | "ms") => v,
+                    v => {
+                        return Err(
+                            format!("Invalid time_unit `{}`, expected `s` or `ms`", v).into()
+                        )
+                    }
+                },
+                None => "ms",
+            },
+            type_aliases: {
+                let mut r = HashMap::new();
+                if let Some(aliases) = config.optional_attribute("type_aliases") {
+                    let aliases = aliases.as_array()?;
+                    for i in 0..aliases.0.elements.len() {
+                        let alias = aliases.index(i)?.as_object()?;
+                        let name = alias.attribute("name")?.as_str()?;
+                        let optional = |key: &str| -> Result<&'static str, Error> {
+                            match alias.optional_attribute(key) {
+                                Some(v) => Ok(v.as_str()?),
+                                None => Ok(""),
+                            }
+                        };
+                        let value = TypeAlias {
+                            ty: alias.attribute("type")?.as_str()?,
+                            fk: optional("fk")?,
+                            default: optional("default")?,
+                        };
+                        if !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                            return Err(format!("Invalid type alias name `{}`", name).into());
+                        }
+                        if r.insert(name, value).is_some() {
+                            return Err(format!("Duplicate type alias `{}`", name).into());
+                        }
+                    }
+                }
+                r
+            },
+            languages: match config.optional_attribute("languages") {
+                Some(v) => {
+                    let mut r = Vec::new();
+                    let langs = v.as_array()?;
+                    for i in 0..langs.0.elements.len() {
+                        let e = langs.index(i)?.as_str()?;
+                        if r.iter().any(|v: &&str| v.eq_ignore_ascii_case(e)) {
+                            return Err(format!("Duplicate language `{}`", e).into());
+                        }
+                        r.push(e);
+                    }
+                    if r.is_empty() {
+                        return Err("`languages` can't be empty".into());
+                    }
+                    r
+                }
+                None => vec!["CN", "CNH", "EN", "JP"],
+            },
+            pseudo_language: match config.optional_attribute("pseudo_language") {
+                Some(v) => {
+                    let pseudo = v.as_object()?;
+                    let optional =
+                        |key: &str, default: &'static str| -> Result<&'static str, Error> {
+                            match pseudo.optional_attribute(key) {
+                                Some(v) => Ok(v.as_str()?),
+                                None => Ok(default),
+                            }
+                        };
+                    Some(PseudoLanguage {
+                        name: optional("name", "PSEUDO")?,
+                        base: pseudo.attribute("base")?.as_str()?,
+                        expansion: match pseudo.optional_attribute("expansion") {
+                            Some(v) => v.as_f32()?,
+                            None => 0.3,
+                        },
+                        markers: (optional("prefix", "[")?, optional("suffix", "]")?),
+                    })
+                }
+                None => None,
+            },
+            nested_language_keys: match config.optional_attribute("nested_language_keys") {
+                Some(v) => v.as_bool()?,
+                None => false,
+            },
+        })
+        .and_then(|v| {
+            if let Some(pseudo) = v.pseudo_language.as_ref() {
+                if v.language(pseudo.base).is_none() {
+                    return Err(format!(
+                        "Invalid base language `{}` of the pseudo language",
+                        pseudo.base
+                    )
+                    .into());
+                }
+                if v.language(pseudo.name).is_some() {
+                    return Err(format!(
+                        "The pseudo language `{}` is a real language",
+                        pseudo.name
+                    )
+                    .into());
+                }
+            }
+            Ok(v)
         })
     }
+
+    /// the configured spelling of a language option, `None` if it isn't configured
+    pub fn language(&self, option: &str) -> Option<&'static str> {
+        self.languages
+            .iter()
+            .find(|v| v.eq_ignore_ascii_case(option.trim()))
+            .copied()
+    }
+
+    /// the language the texts are written in, translations are made from it
+    pub fn source_language(&self) -> &'static str {
+        self.languages[0]
+    }
+
+    /// the pseudo language if `lang` is it
+    pub fn pseudo(&self, lang: &str) -> Option<&PseudoLanguage> {
+        self.pseudo_language
+            .as_ref()
+            .filter(|v| v.name.eq_ignore_ascii_case(lang.trim()))
+    }
+
+    /// the language whose texts `lang` is built from
+    pub fn text_language(&self, lang: &'static str) -> &'static str {
+        self.pseudo(lang).map_or(lang, |v| v.base)
+    }
+
+    /// `all` or a comma separated list of languages, the pseudo language is only built if it's
+    /// listed
+    pub fn language_options(&self, option: &str) -> Result<Vec<&'static str>, Error> {
+        if option.trim().eq_ignore_ascii_case("all") {
+            return Ok(self.languages.clone());
+        }
+        let mut ret = Vec::new();
+        for v in option.split(',') {
+            let lang = match self.pseudo(v) {
+                Some(pseudo) => Some(pseudo.name),
+                None => self.language(v),
+            };
+            let lang = lang.ok_or::<Error>(
+                format!(
+                    "Invalid language option: {}, expected `all` or some of {:?}",
+                    v.trim(),
+                    self.languages
+                )
+                .into(),
+            )?;
+            if !ret.contains(&lang) {
+                ret.push(lang);
+            }
+        }
+        Ok(ret)
+    }
 }
 
 pub static CFG: LazyLock<Config> = LazyLock::new(|| {
@@ -94,6 +278,25 @@ pub static mut SOURCE_XLSXS_DIR: &'static str = "D:/config";
 pub static mut REF_TEXT_DIR: &'static str = "ConfigRefNameMapping/";
 pub static mut CONFIG_COLLECTION_PATH: &'static str = "";
 pub static mut LANG_OUTPUT_DIR: &'static str = "Data/";
+
+/// imported translations of `lang`, they live with the source tables
+pub fn translation_path(lang: &str) -> String {
+    format!("{}/Translations/{}.po", unsafe { SOURCE_XLSXS_DIR }, lang)
+}
+
+/// source text hashes of the translations of `lang` in the LString sheets
+pub fn translation_hash_path(lang: &str) -> String {
+    format!(
+        "{}/Translations/{}.hash.txt",
+        unsafe { SOURCE_XLSXS_DIR },
+        lang
+    )
+}
+
+/// output dir of the language files of `lang`
+pub fn lang_output_dir(lang: &str) -> String {
+    format!("{}/Language_{}", unsafe { LANG_OUTPUT_DIR }, lang)
+}
 pub static TABLE_XLSX_FILTER: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
     let mut ret = HashSet::new();
     for v in CFG.ban_list.iter() {
@@ -120,3 +323,161 @@ pub static ENUM_FLAGS_FILTER: LazyLock<HashSet<&'static str>> = LazyLock::new(||
     ret
 });
 pub const MAGIC: &'static [u8] = &[0xFF, 0xFE, 0xF0, 0xAE];
+
+static EXPANDED_TYPES: LazyLock<DashMap<String, &'static str>> = LazyLock::new(DashMap::new);
+
+/// replace the aliases in a type expression, e.g. `List<ItemRef>` becomes `List<int>`,
+/// returns `None` if there's no alias in it
+pub fn expand_type_alias(ty: &str) -> Result<Option<&'static str>, Error> {
+    if let Some(v) = EXPANDED_TYPES.get(ty) {
+        return Ok(Some(*v));
+    }
+    match expand_with(ty, &CFG.type_aliases)? {
+        Some(v) => {
+            let v: &'static str = Box::leak(v.into_boxed_str());
+            EXPANDED_TYPES.insert(ty.to_string(), v);
+            Ok(Some(v))
+        }
+        None => Ok(None),
+    }
+}
+
+fn expand_with(
+    ty: &str,
+    aliases: &HashMap<&'static str, TypeAlias>,
+) -> Result<Option<String>, Error> {
+    if aliases.is_empty() {
+        return Ok(None);
+    }
+
+    let mut cur = ty.trim().to_string();
+    let mut expanded = false;
+    // aliases may refer to other aliases, but not to themselves
+    for _ in 0..16 {
+        let mut ret = String::with_capacity(cur.len());
+        let mut replaced = false;
+        let mut start = None;
+        for (i, c) in cur.char_indices().chain([(cur.len(), ' ')]) {
+            // `.` is a part of the ident, so that `Config.Reward` isn't matched by `Reward`
+            if c.is_alphanumeric() || c == '_' || c == '.' {
+                start.get_or_insert(i);
+                continue;
+            }
+            if let Some(s) = start.take() {
+                match aliases.get(&cur[s..i]) {
+                    Some(alias) => {
+                        ret.push_str(alias.ty);
+                        replaced = true;
+                    }
+                    None => ret.push_str(&cur[s..i]),
+                }
+            }
+            if i < cur.len() {
+                ret.push(c);
+            }
+        }
+        if !replaced {
+            if !expanded {
+                return Ok(None);
+            }
+            return Ok(Some(cur));
+        }
+        expanded = true;
+        cur = ret;
+    }
+    Err(format!("The type aliases in `{}` are recursive", ty).into())
+}
+
+/// find an alias with a fk pattern that is nested in the type expression, e.g. `ItemRef` in
+/// `List<ItemRef>`, its fk pattern can't be applied to the elements
+pub fn nested_fk_alias(ty: &str) -> Option<&'static str> {
+    nested_fk_alias_with(ty, &CFG.type_aliases, true, 0)
+}
+
+fn nested_fk_alias_with(
+    ty: &str,
+    aliases: &HashMap<&'static str, TypeAlias>,
+    top: bool,
+    depth: usize,
+) -> Option<&'static str> {
+    // the recursive aliases are reported by `expand_with`
+    if depth > 16 {
+        return None;
+    }
+    let ty = ty.trim();
+    ty.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+        .filter_map(|v| aliases.get_key_value(v))
+        .find_map(|(name, alias)| {
+            let whole = top && *name == ty;
+            if !whole && !alias.fk.trim().is_empty() {
+                return Some(*name);
+            }
+            nested_fk_alias_with(alias.ty, aliases, whole, depth + 1)
+        })
+}
+
+#[test]
+fn test_expand_type_alias() {
+    let mut aliases = HashMap::new();
+    let mut alias = |name, ty| {
+        aliases.insert(
+            name,
+            TypeAlias {
+                ty,
+                fk: "",
+                default: "",
+            },
+        );
+    };
+    alias("ItemRef", "int");
+    alias("Reward", "ValueTuple<ItemRef,int>");
+    alias("RewardList", "List<Reward>");
+    alias("Loop", "List<Loop>");
+
+    assert_eq!(
+        expand_with("RewardList", &aliases).unwrap().as_deref(),
+        Some("List<ValueTuple<int,int>>")
+    );
+    assert_eq!(
+        expand_with("ItemRef[]", &aliases).unwrap().as_deref(),
+        Some("int[]")
+    );
+    assert_eq!(
+        expand_with("Config.Reward", &aliases).unwrap().as_deref(),
+        None
+    );
+    assert_eq!(expand_with("int", &aliases).unwrap().as_deref(), None);
+    assert!(expand_with("Loop", &aliases).is_err());
+
+    aliases.get_mut("ItemRef").unwrap().fk = "*Item";
+    assert_eq!(nested_fk_alias_with("ItemRef", &aliases, true, 0), None);
+    assert_eq!(
+        nested_fk_alias_with("List<ItemRef>", &aliases, true, 0),
+        Some("ItemRef")
+    );
+    assert_eq!(
+        nested_fk_alias_with("RewardList", &aliases, true, 0),
+        Some("ItemRef")
+    );
+    assert_eq!(nested_fk_alias_with("List<int>", &aliases, true, 0), None);
+}
+
+#[test]
+fn test_language_options() {
+    // the sample pseudo language in config.tnl
+    let tnl = std::fs::read_to_string("config.tnl")
+        .unwrap()
+        .replace("// pseudo_language:", "pseudo_language:");
+    let tnl: &'static str = Box::leak(tnl.into_boxed_str());
+    let cfg = Config::load(Box::leak(Box::new(tnl::Object::try_from(tnl).unwrap()))).unwrap();
+
+    assert_eq!(cfg.language_options("all").unwrap(), cfg.languages);
+    assert_eq!(cfg.language_options("cn, en,CN").unwrap(), vec!["CN", "EN"]);
+    assert!(cfg.language_options("CN,XX").is_err());
+    assert!(!cfg.language_options("all").unwrap().contains(&"PSEUDO"));
+    assert_eq!(
+        cfg.language_options("cn,pseudo").unwrap(),
+        vec!["CN", "PSEUDO"]
+    );
+    assert_eq!(cfg.text_language("PSEUDO"), "EN");
+}
@@ -0,0 +1,217 @@
+use std::borrow::Cow;
+
+// text of a cell is kept verbatim until it's written out, every output target escapes it
+// on its own
+
+/// quote the cell text as a string token of the value parser
+pub fn quote(val: &str) -> String {
+    let mut ret = String::with_capacity(val.len() + 2);
+    ret.push('"');
+    for c in val.chars() {
+        if c == '\\' || c == '"' {
+            ret.push('\\');
+        }
+        ret.push(c);
+    }
+    ret.push('"');
+    ret
+}
+
+/// the inverse of `quote`, `raw` is the content between the quotes
+pub fn unquote(raw: &str) -> Cow<'_, str> {
+    if !raw.contains('\\') {
+        return Cow::Borrowed(raw);
+    }
+    let mut ret = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some(c) => ret.push(c),
+                None => ret.push('\\'),
+            }
+        } else {
+            ret.push(c);
+        }
+    }
+    Cow::Owned(ret)
+}
+
+/// regular C# string literal, `\u0085`, `\u2028` and `\u2029` are line terminators for C# too
+pub fn csharp(val: &str) -> String {
+    let mut ret = String::with_capacity(val.len() + 2);
+    ret.push('"');
+    for c in val.chars() {
+        match c {
+            '\\' => ret.push_str("\\\\"),
+            '"' => ret.push_str("\\\""),
+            '\n' => ret.push_str("\\n"),
+            '\r' => ret.push_str("\\r"),
+            '\t' => ret.push_str("\\t"),
+            '\0' => ret.push_str("\\0"),
+            c if c.is_control() || c == '\u{2028}' || c == '\u{2029}' => {
+                ret.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+/// JSON string including the quotes
+pub fn json(val: &str) -> String {
+    let mut ret = String::with_capacity(val.len() + 2);
+    ret.push('"');
+    for c in val.chars() {
+        match c {
+            '\\' => ret.push_str("\\\\"),
+            '"' => ret.push_str("\\\""),
+            '\n' => ret.push_str("\\n"),
+            '\r' => ret.push_str("\\r"),
+            '\t' => ret.push_str("\\t"),
+            '\u{8}' => ret.push_str("\\b"),
+            '\u{c}' => ret.push_str("\\f"),
+            c if (c as u32) < 0x20 || c == '\u{2028}' || c == '\u{2029}' => {
+                ret.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+/// gettext PO string including the quotes
+pub fn po(val: &str) -> String {
+    let mut ret = String::with_capacity(val.len() + 2);
+    ret.push('"');
+    for c in val.chars() {
+        match c {
+            '\\' => ret.push_str("\\\\"),
+            '"' => ret.push_str("\\\""),
+            '\n' => ret.push_str("\\n"),
+            '\r' => ret.push_str("\\r"),
+            '\t' => ret.push_str("\\t"),
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+/// the inverse of `po`, `raw` is the content between the quotes
+pub fn po_unquote(raw: &str) -> Cow<'_, str> {
+    if !raw.contains('\\') {
+        return Cow::Borrowed(raw);
+    }
+    let mut ret = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => ret.push('\n'),
+                Some('r') => ret.push('\r'),
+                Some('t') => ret.push('\t'),
+                Some(c) => ret.push(c),
+                None => ret.push('\\'),
+            }
+        } else {
+            ret.push(c);
+        }
+    }
+    Cow::Owned(ret)
+}
+
+/// XML text or attribute value
+pub fn xml(val: &str) -> Cow<'_, str> {
+    if !val.contains(['&', '<', '>', '"', '\'']) {
+        return Cow::Borrowed(val);
+    }
+    let mut ret = String::with_capacity(val.len() + 8);
+    for c in val.chars() {
+        match c {
+            '&' => ret.push_str("&amp;"),
+            '<' => ret.push_str("&lt;"),
+            '>' => ret.push_str("&gt;"),
+            '"' => ret.push_str("&quot;"),
+            '\'' => ret.push_str("&apos;"),
+            c => ret.push(c),
+        }
+    }
+    Cow::Owned(ret)
+}
+
+/// the language files hold one text per line, so line breaks are written as `\n` which is what
+/// the designers already type for the runtime, other backslashes are kept as they are
+pub fn language_txt(val: &str) -> Cow<'_, str> {
+    if !val.contains(['\r', '\n']) {
+        return Cow::Borrowed(val);
+    }
+    Cow::Owned(val.replace("\r\n", "\\n").replace(['\r', '\n'], "\\n"))
+}
+
+/// drop the whitespaces outside of string literals
+pub fn strip_spaces(val: &str) -> String {
+    let mut ret = String::with_capacity(val.len());
+    let mut quoted = false;
+    let mut escaped = false;
+    for c in val.chars() {
+        if quoted {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                quoted = false;
+            }
+        } else if c == '"' {
+            quoted = true;
+        } else if c.is_whitespace() {
+            continue;
+        }
+        ret.push(c);
+    }
+    ret
+}
+
+#[test]
+fn test_escape() {
+    let tricky = [
+        "",
+        "plain",
+        "say \"hi\"",
+        "C:\\path\\file",
+        "line1\nline2",
+        "line1\r\nline2",
+        "{0} gets {1}",
+        "tab\there",
+        "trailing \\",
+        "\"",
+    ];
+    for v in tricky {
+        let quoted = quote(v);
+        assert_eq!(unquote(&quoted[1..quoted.len() - 1]), v);
+    }
+
+    assert_eq!(csharp("say \"hi\""), r#""say \"hi\"""#);
+    assert_eq!(csharp("C:\\path"), r#""C:\\path""#);
+    assert_eq!(csharp("a\r\nb"), r#""a\r\nb""#);
+    assert_eq!(csharp("{0}/{1}"), r#""{0}/{1}""#);
+    assert_eq!(csharp("\u{1}\u{2028}"), r#""\u0001\u2028""#);
+    assert_eq!(json("a\"b\\c\nd\u{8}"), r#""a\"b\\c\nd\b""#);
+    assert_eq!(json("\u{1f}"), r#""\u001f""#);
+    assert_eq!(po("a\"b\\c\nd"), r#""a\"b\\c\nd""#);
+    for v in tricky {
+        let quoted = po(v);
+        assert_eq!(po_unquote(&quoted[1..quoted.len() - 1]), v);
+    }
+    assert_eq!(
+        xml("<b>Tom & \"Jerry\"</b>"),
+        "&lt;b&gt;Tom &amp; &quot;Jerry&quot;&lt;/b&gt;"
+    );
+    assert_eq!(language_txt("a\r\nb\nc"), "a\\nb\\nc");
+    assert_eq!(language_txt("keep \\n"), "keep \\n");
+    assert_eq!(strip_spaces("{1, \"a b\", 2}"), "{1,\"a b\",2}");
+    assert_eq!(strip_spaces("{\"a \\\" b\" , c}"), "{\"a \\\" b\",c}");
+}
@@ -0,0 +1,415 @@
+use crate::{error::Error, types::TypeInfo};
+use std::collections::HashMap;
+
+/// result of a constant expression, integers stay exact until they meet a float
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Number {
+    Int(i128),
+    Float(f64),
+}
+
+impl Number {
+    fn as_f64(self) -> f64 {
+        match self {
+            Number::Int(v) => v as f64,
+            Number::Float(v) => v,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Num(Number),
+    Ident(&'a str),
+    Op(char),
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token<'_>>, Error> {
+    let mut ret = Vec::new();
+    let bytes = src.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i] as char;
+        if c.is_ascii_whitespace() {
+            i += 1;
+        } else if "+-*/%()".contains(c) {
+            ret.push(Token::Op(c));
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            if src[i..].starts_with("0x") || src[i..].starts_with("0X") {
+                i += 2;
+                while i < bytes.len() && bytes[i].is_ascii_hexdigit() {
+                    i += 1;
+                }
+                let v = i128::from_str_radix(&src[start + 2..i], 16)
+                    .map_err(|_| format!("invalid number `{}`", &src[start..i]))?;
+                ret.push(Token::Num(Number::Int(v)));
+                continue;
+            }
+            while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+                i += 1;
+            }
+            let raw = &src[start..i];
+            let v = if raw.contains('.') {
+                Number::Float(
+                    raw.parse()
+                        .map_err(|_| format!("invalid number `{}`", raw))?,
+                )
+            } else {
+                Number::Int(
+                    raw.parse()
+                        .map_err(|_| format!("invalid number `{}`", raw))?,
+                )
+            };
+            ret.push(Token::Num(v));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < bytes.len()
+                && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] == b'.')
+            {
+                i += 1;
+            }
+            ret.push(Token::Ident(&src[start..i]));
+        } else {
+            return Err(format!("unexpected character `{}` in expression `{}`", c, src).into());
+        }
+    }
+    Ok(ret)
+}
+
+struct Evaluator<'a, 'b> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    consts: &'b HashMap<String, Number>,
+}
+
+impl Evaluator<'_, '_> {
+    fn peek_op(&self) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(c)) => Some(*c),
+            _ => None,
+        }
+    }
+
+    // expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<Number, Error> {
+        let mut lhs = self.term()?;
+        while let Some(op @ ('+' | '-')) = self.peek_op() {
+            self.pos += 1;
+            let rhs = self.term()?;
+            lhs = binary(op, lhs, rhs)?;
+        }
+        Ok(lhs)
+    }
+
+    // term := unary (('*' | '/' | '%') unary)*
+    fn term(&mut self) -> Result<Number, Error> {
+        let mut lhs = self.unary()?;
+        while let Some(op @ ('*' | '/' | '%')) = self.peek_op() {
+            self.pos += 1;
+            let rhs = self.unary()?;
+            lhs = binary(op, lhs, rhs)?;
+        }
+        Ok(lhs)
+    }
+
+    // unary := '-' unary | primary
+    fn unary(&mut self) -> Result<Number, Error> {
+        if self.peek_op() == Some('-') {
+            self.pos += 1;
+            return match self.unary()? {
+                Number::Int(v) => Ok(Number::Int(
+                    v.checked_neg().ok_or::<Error>("integer overflow".into())?,
+                )),
+                Number::Float(v) => Ok(Number::Float(-v)),
+            };
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Number, Error> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        match token {
+            Some(Token::Num(v)) => Ok(*v),
+            Some(Token::Ident(ident)) => self
+                .consts
+                .get(*ident)
+                .copied()
+                .ok_or(format!("unknown constant `{}`", ident).into()),
+            Some(Token::Op('(')) => {
+                let v = self.expr()?;
+                if self.peek_op() != Some(')') {
+                    return Err("expected `)`".into());
+                }
+                self.pos += 1;
+                Ok(v)
+            }
+            Some(Token::Op(c)) => Err(format!("unexpected operator `{}`", c).into()),
+            None => Err("unexpected end of expression".into()),
+        }
+    }
+}
+
+fn binary(op: char, lhs: Number, rhs: Number) -> Result<Number, Error> {
+    let overflow =
+        || -> Error { format!("integer overflow in `{:?} {} {:?}`", lhs, op, rhs).into() };
+    match (lhs, rhs) {
+        (Number::Int(a), Number::Int(b)) => match op {
+            '+' => a.checked_add(b).map(Number::Int).ok_or_else(overflow),
+            '-' => a.checked_sub(b).map(Number::Int).ok_or_else(overflow),
+            '*' => a.checked_mul(b).map(Number::Int).ok_or_else(overflow),
+            '/' | '%' if b == 0 => Err("division by zero".into()),
+            // inexact division falls back to a float, integer columns reject it later
+            '/' if a % b == 0 => Ok(Number::Int(a / b)),
+            '/' => Ok(Number::Float(a as f64 / b as f64)),
+            '%' => Ok(Number::Int(a % b)),
+            _ => unreachable!(),
+        },
+        _ => {
+            let (a, b) = (lhs.as_f64(), rhs.as_f64());
+            match op {
+                '+' => Ok(Number::Float(a + b)),
+                '-' => Ok(Number::Float(a - b)),
+                '*' => Ok(Number::Float(a * b)),
+                '/' if b == 0.0 => Err("division by zero".into()),
+                '/' => Ok(Number::Float(a / b)),
+                _ => Err(format!("operator `{}` needs integer operands", op).into()),
+            }
+        }
+    }
+}
+
+pub fn eval(src: &str, consts: &HashMap<String, Number>) -> Result<Number, Error> {
+    let src = src.trim();
+    let src = src.strip_prefix('=').unwrap_or(src);
+    let mut evaluator = Evaluator {
+        tokens: tokenize(src)?,
+        pos: 0,
+        consts,
+    };
+    let v = evaluator.expr()?;
+    if evaluator.pos != evaluator.tokens.len() {
+        return Err(format!("unexpected trailing tokens in expression `{}`", src).into());
+    }
+    Ok(v)
+}
+
+/// the value of a plain number literal like `-2`, `0x10`, `1.5f` or `1e-3`
+fn literal(val: &str) -> Option<Number> {
+    let (minus, val) = match val.strip_prefix('-') {
+        Some(v) => (true, v),
+        None => (false, val.strip_prefix('+').unwrap_or(val)),
+    };
+    if !val.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    let v = if let Some(hex) = val.strip_prefix("0x").or_else(|| val.strip_prefix("0X")) {
+        Number::Int(i128::from_str_radix(hex, 16).ok()?)
+    } else if let Ok(v) = val.parse::<i128>() {
+        Number::Int(v)
+    } else {
+        // the suffixes of C# literals
+        let val = val
+            .strip_suffix(['f', 'F', 'd', 'D', 'm', 'M'])
+            .or_else(|| {
+                [
+                    "ul", "UL", "uL", "Ul", "lu", "LU", "lU", "Lu", "u", "U", "l", "L",
+                ]
+                .iter()
+                .find_map(|v| val.strip_suffix(v))
+            })
+            .unwrap_or(val);
+        match val.parse::<i128>() {
+            Ok(v) => Number::Int(v),
+            Err(_) => Number::Float(val.parse::<f64>().ok().filter(|v| v.is_finite())?),
+        }
+    };
+    Some(match v {
+        Number::Int(v) if minus => Number::Int(-v),
+        Number::Float(v) if minus => Number::Float(-v),
+        v => v,
+    })
+}
+
+/// a cell is an expression when it starts with `=`, names a constant or has an operator after
+/// its first token, plain literals are left to the parser
+fn looks_like_expression(val: &str, is_const: impl Fn(&str) -> bool) -> bool {
+    let val = val.trim();
+    if val.starts_with('=') {
+        return true;
+    }
+    if val.is_empty() || literal(val).is_some() {
+        return false;
+    }
+    is_const(val)
+        || val
+            .char_indices()
+            .skip(1)
+            .any(|(_, c)| "+-*/%()".contains(c))
+}
+
+pub fn is_expression(val: &str, consts: &HashMap<String, Number>) -> bool {
+    looks_like_expression(val, |v| consts.contains_key(v))
+}
+
+/// evaluate `src` and render it as a literal of `ty`, checking the range of integer types
+pub fn eval_literal(
+    src: &str,
+    ty: &TypeInfo,
+    consts: &HashMap<String, Number>,
+) -> Result<String, Error> {
+    let v = eval(src, consts).map_err(|e| format!("evaluate `{}` failed: {}", src, e))?;
+    let (min, max) = match ty {
+        TypeInfo::Int => (i32::MIN as i128, i32::MAX as i128),
+        TypeInfo::Uint => (0, u32::MAX as i128),
+        TypeInfo::Short => (i16::MIN as i128, i16::MAX as i128),
+        TypeInfo::UShort => (0, u16::MAX as i128),
+        TypeInfo::Byte => (0, u8::MAX as i128),
+        TypeInfo::SByte => (i8::MIN as i128, i8::MAX as i128),
+        TypeInfo::Long => (i64::MIN as i128, i64::MAX as i128),
+        TypeInfo::ULong => (0, u64::MAX as i128),
+        TypeInfo::Float | TypeInfo::Double | TypeInfo::Decimal => {
+            let v = v.as_f64();
+            if !v.is_finite() || (ty == &TypeInfo::Float && v.abs() > f32::MAX as f64) {
+                return Err(format!("`{}` = {} is out of the range of {}", src, v, ty).into());
+            }
+            return Ok(v.to_string());
+        }
+        _ => return Err(format!("expression `{}` is not allowed for type {}", src, ty).into()),
+    };
+    match v {
+        Number::Int(v) if v >= min && v <= max => Ok(v.to_string()),
+        Number::Int(v) => Err(format!("`{}` = {} is out of the range of {}", src, v, ty).into()),
+        Number::Float(v) => {
+            Err(format!("`{}` = {} is not an integer of type {}", src, v, ty).into())
+        }
+    }
+}
+
+/// numeric constants of the GlobalConfig sheet, reachable as `Ident` and `{table}.Ident`,
+/// values may refer to other constants
+pub fn load_constants(
+    table: &str,
+    rows: &[(&str, &str)],
+) -> Result<HashMap<String, Number>, Error> {
+    fn resolve(
+        idx: usize,
+        rows: &[(&str, &str)],
+        table: &str,
+        consts: &mut HashMap<String, Number>,
+        stack: &mut Vec<usize>,
+    ) -> Result<Number, Error> {
+        let (ident, val) = rows[idx];
+        if let Some(v) = consts.get(ident) {
+            return Ok(*v);
+        }
+        if stack.contains(&idx) {
+            return Err(format!("constant `{}` refers to itself", ident).into());
+        }
+        let v = match literal(val.trim()) {
+            Some(v) => v,
+            None => {
+                stack.push(idx);
+                let tokens = tokenize(val.trim().strip_prefix('=').unwrap_or(val.trim()))?;
+                for token in tokens {
+                    let Token::Ident(dep) = token else { continue };
+                    let dep = dep
+                        .strip_prefix(table)
+                        .and_then(|v| v.strip_prefix('.'))
+                        .unwrap_or(dep);
+                    if let Some(i) = rows.iter().position(|v| v.0 == dep) {
+                        resolve(i, rows, table, consts, stack)?;
+                    }
+                }
+                stack.pop();
+                eval(val, consts).map_err(|e| format!("constant `{}`: {}", ident, e))?
+            }
+        };
+        consts.insert(ident.to_string(), v);
+        consts.insert(format!("{}.{}", table, ident), v);
+        Ok(v)
+    }
+
+    // values which are neither literals nor expressions are left to the value parser
+    let rows = rows
+        .iter()
+        .filter(|(_, val)| {
+            literal(val.trim()).is_some()
+                || looks_like_expression(val, |v| {
+                    let v = v
+                        .strip_prefix(table)
+                        .and_then(|v| v.strip_prefix('.'))
+                        .unwrap_or(v);
+                    rows.iter().any(|r| r.0 == v)
+                })
+        })
+        .copied()
+        .collect::<Vec<_>>();
+    let mut consts = HashMap::new();
+    let mut stack = Vec::new();
+    for idx in 0..rows.len() {
+        resolve(idx, &rows, table, &mut consts, &mut stack)?;
+    }
+    Ok(consts)
+}
+
+#[test]
+fn test_eval() {
+    let consts = load_constants(
+        "GlobalConfig",
+        &[
+            ("MaxHp", "=BaseHp * 2 + 10"),
+            ("BaseHp", "100"),
+            ("MaxLevel", "60"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(consts.get("MaxHp"), Some(&Number::Int(210)));
+    assert_eq!(
+        eval_literal("GlobalConfig.MaxLevel", &TypeInfo::Int, &consts).unwrap(),
+        "60"
+    );
+    assert_eq!(
+        eval_literal("3600 * 24", &TypeInfo::Int, &consts).unwrap(),
+        "86400"
+    );
+    assert_eq!(
+        eval_literal("-(1 + 2) * 3", &TypeInfo::SByte, &consts).unwrap(),
+        "-9"
+    );
+    assert_eq!(
+        eval_literal("=MaxLevel / 8", &TypeInfo::Float, &consts).unwrap(),
+        "7.5"
+    );
+    assert!(eval_literal("=MaxLevel / 8", &TypeInfo::Int, &consts).is_err());
+    assert!(eval_literal("=BaseHp * 3", &TypeInfo::SByte, &consts).is_err());
+    assert!(eval_literal("Unknown + 1", &TypeInfo::Int, &consts).is_err());
+    assert!(is_expression("MaxLevel", &consts));
+    assert!(!is_expression("-5", &consts));
+    assert!(!is_expression("1.5e-3", &consts));
+    assert!(!is_expression("-1.5f", &consts));
+    assert!(!is_expression("1e-3", &consts));
+    assert!(!is_expression("100L", &consts));
+    assert!(is_expression("2-1", &consts));
+
+    // plain literals are constants as they are, empty cells are left out
+    let consts = load_constants(
+        "GlobalConfig",
+        &[
+            ("Empty", ""),
+            ("Speed", "1.5f"),
+            ("Rate", "1e-3"),
+            ("Offset", "-2"),
+            ("Total", "=Offset * 10"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(consts.get("Empty"), None);
+    assert_eq!(consts.get("Speed"), Some(&Number::Float(1.5)));
+    assert_eq!(consts.get("Rate"), Some(&Number::Float(1e-3)));
+    assert_eq!(consts.get("Offset"), Some(&Number::Int(-2)));
+    assert_eq!(consts.get("Total"), Some(&Number::Int(-20)));
+    assert!(load_constants("GlobalConfig", &[("A", "=B"), ("B", "=A")]).is_err());
+}
@@ -13,21 +13,26 @@ use std::{
     thread::JoinHandle,
 };
 
-use ansi_term::Colour::Red;
-use args::{Args, LanguageOption};
+use ansi_term::Colour::{Red, Yellow};
+use args::Args;
 use clap::Parser;
 use config::{
     CFG, CONFIG_COLLECTION_PATH, LANG_OUTPUT_DIR, OUTPUT_ENUM_CODE_DIR, OUTPUT_SCRIPT_CODE_DIR,
     OUTPUT_SERVER_ENUM_CODE_DIR, OUTPUT_SERVER_SCRIPT_CODE_DIR, REF_TEXT_DIR, SOURCE_XLSXS_DIR,
 };
-use table::{Generator, TableEntity};
+use table::{
+    Catalog, FkGraph, Generator, GraphFormat, Overlay, Report, TableEntity, TranslationFormat,
+};
 
 mod args;
 mod config;
 mod error;
+mod escape;
+mod expr;
 mod lex;
 mod parser;
 mod preconfig;
+mod schema;
 mod table;
 mod types;
 mod util;
@@ -50,7 +55,7 @@ pub static THREADS: LazyLock<rayon::ThreadPool> = LazyLock::new(|| {
         .unwrap()
 });
 
-fn create_dest_dirs(args: &Args) {
+fn create_dest_dirs(args: &Args) -> Vec<&'static str> {
     if let Err(_) = fs::metadata(unsafe { OUTPUT_SCRIPT_CODE_DIR }) {
         if let Err(_) = fs::create_dir_all(unsafe { OUTPUT_SCRIPT_CODE_DIR }) {
             exit(-1)
@@ -81,33 +86,26 @@ fn create_dest_dirs(args: &Args) {
         }
     }
 
-    let mut lang_path = args.output_lang_dir.clone();
-    let option = args.loption.as_str().into();
-    match option {
-        LanguageOption::CN => lang_path.push_str("/Language_CN"),
-        LanguageOption::CNH => lang_path.push_str("/Language_CNH"),
-        LanguageOption::EN => lang_path.push_str("/Language_EN"),
-        LanguageOption::JP => lang_path.push_str("/Language_JP"),
-        LanguageOption::Invalid => {
-            eprintln!(
-                "{}",
-                Red.bold().paint(format!(
-                    "[Error]: Invalid language option: {}",
-                    args.loption
-                ))
-            );
-        }
-    }
-
-    if let Err(_) = fs::metadata(lang_path.as_str()) {
-        if let Err(_) = fs::create_dir_all(lang_path.as_str()) {
+    let loptions = match CFG.language_options(&args.loption) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{}", Red.bold().paint(format!("[Error]: {}", e)));
             exit(-1)
         }
-    }
+    };
 
     unsafe {
-        LANG_OUTPUT_DIR = Box::leak(lang_path.into_boxed_str());
+        LANG_OUTPUT_DIR = Box::leak(args.output_lang_dir.clone().into_boxed_str());
+    }
+    for lang in loptions.iter() {
+        let lang_path = config::lang_output_dir(lang);
+        if let Err(_) = fs::metadata(lang_path.as_str()) {
+            if let Err(_) = fs::create_dir_all(lang_path.as_str()) {
+                exit(-1)
+            }
+        }
     }
+    loptions
 }
 
 fn update_git() {
@@ -173,8 +171,9 @@ fn load_tables<P: AsRef<Path>>(
 
 fn build(
     tables: Arc<util::AtomicLinkedList<TableEntity>>,
-    loption: &str,
+    loptions: &[&'static str],
     lstring: bool,
+    compact_lstrings: bool,
 ) -> Result<(), error::Error> {
     // SAFETY: no data-race here, read-only
     let mut tables = unsafe {
@@ -186,13 +185,140 @@ fn build(
     tables.sort_by(|a, b| a.name().cmp(b.name()));
     let genarator = Generator {
         entities: tables,
-        loption,
+        loptions,
         lstring,
+        compact_lstrings,
     };
     genarator.build()?;
     Ok(())
 }
 
+/// load every table and wait for the loading threads, sorted by name
+fn load_all_tables(
+    excluded: Arc<ExcludedFolders<'static>>,
+) -> Result<Vec<TableEntity>, error::Error> {
+    let (tx, rx) = std::sync::mpsc::channel::<JoinHandle<()>>();
+    let tables = Arc::new(util::AtomicLinkedList::new());
+    load_tables(unsafe { SOURCE_XLSXS_DIR }, tx, excluded, tables.clone())?;
+    while let Ok(handle) = rx.recv() {
+        let _ = handle.join();
+    }
+    // SAFETY: all loading threads are joined
+    let mut tables = unsafe {
+        Arc::into_inner(tables)
+            .ok_or::<error::Error>("".into())?
+            .into_unsafe_vector()
+    };
+    tables.sort_by(|a, b| a.name().cmp(b.name()));
+    Ok(tables)
+}
+
+fn graph(
+    excluded: Arc<ExcludedFolders<'static>>,
+    format: GraphFormat,
+    output: &str,
+) -> Result<(), error::Error> {
+    let tables = load_all_tables(excluded)?;
+    let graph = FkGraph::build(&tables)?;
+    for (name, cols) in graph.self_references() {
+        eprintln!(
+            "{}",
+            Yellow.bold().paint(format!(
+                "[Warning]: Table {} references itself by {:?}",
+                name, cols
+            ))
+        );
+    }
+    for cycle in graph.cycles() {
+        eprintln!(
+            "{}",
+            Yellow.bold().paint(format!(
+                "[Warning]: Found fk cycle between tables: {}",
+                cycle.join(" <-> ")
+            ))
+        );
+    }
+
+    let mut stream: Box<dyn std::io::Write> = if output.is_empty() {
+        Box::new(std::io::stdout())
+    } else {
+        Box::new(fs::File::create(output)?)
+    };
+    match format {
+        GraphFormat::Dot => graph.write_dot(&mut stream)?,
+        GraphFormat::Mermaid => graph.write_mermaid(&mut stream)?,
+        GraphFormat::Invalid => unreachable!(),
+    }
+    stream.flush()?;
+    Ok(())
+}
+
+fn lang_export(
+    excluded: Arc<ExcludedFolders<'static>>,
+    format: TranslationFormat,
+    output: &str,
+    loptions: &[&'static str],
+) -> Result<(), error::Error> {
+    let langs = loptions
+        .iter()
+        .filter(|v| **v != CFG.source_language() && CFG.pseudo(v).is_none())
+        .collect::<Vec<_>>();
+    if langs.is_empty() {
+        return Err(format!(
+            "Nothing to export, {} is the source language",
+            CFG.source_language()
+        )
+        .into());
+    }
+    fs::create_dir_all(output)?;
+    let tables = load_all_tables(excluded)?;
+    for lang in langs {
+        let catalog = Catalog::collect(&tables, lang)?;
+        let path = Path::new(output).join(format!("{}.{}", lang, format.extension()));
+        let mut stream = fs::File::create(&path)?;
+        match format {
+            TranslationFormat::Xliff => catalog.write_xliff(&mut stream)?,
+            TranslationFormat::Po => catalog.write_po(&mut stream)?,
+            TranslationFormat::Invalid => unreachable!(),
+        }
+        std::io::Write::flush(&mut stream)?;
+        println!("{} texts => {}", catalog.units.len(), path.display());
+    }
+    Ok(())
+}
+
+fn lang_report(excluded: Arc<ExcludedFolders<'static>>, output: &str) -> Result<(), error::Error> {
+    let tables = load_all_tables(excluded)?;
+    let report = Report::collect(&tables, &CFG.languages)?;
+    let mut stream: Box<dyn std::io::Write> = if output.is_empty() {
+        Box::new(std::io::stdout())
+    } else {
+        Box::new(fs::File::create(output)?)
+    };
+    report.write(&mut stream)?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn lang_import(files: &[String]) -> Result<(), error::Error> {
+    for v in files {
+        let catalog = Catalog::read(v)?;
+        let lang = catalog.lang.clone();
+        let count = Overlay::import(catalog).map_err(|e| format!("Import {} failed: {}", v, e))?;
+        println!("{} translations of {} <= {}", count, lang, v);
+    }
+    Ok(())
+}
+
+fn excluded_folders(raw: &str) -> Arc<ExcludedFolders<'static>> {
+    let mut excluded_folders = ExcludedFolders::default();
+    let exstr = Box::leak(raw.replace(" ", "").into_boxed_str());
+    for v in exstr.split(",") {
+        excluded_folders.0.insert(v);
+    }
+    Arc::new(excluded_folders)
+}
+
 fn wait_for_user_input() {
     println!("\nPress any key to exit the program...");
     let mut empty = [0; 1];
@@ -204,7 +330,7 @@ struct ExcludedFolders<'a>(HashSet<&'a str>);
 
 fn main() {
     let args = Args::parse();
-    create_dest_dirs(&args);
+    let loptions = create_dest_dirs(&args);
     unsafe {
         OUTPUT_SCRIPT_CODE_DIR = Box::leak(args.output_script_dir.into_boxed_str());
         OUTPUT_ENUM_CODE_DIR = Box::leak(args.output_enum_dir.into_boxed_str());
@@ -220,12 +346,7 @@ fn main() {
             println!("[Begin]");
 
             // excluded folders
-            let mut excluded_folders = ExcludedFolders::default();
-            let exstr = Box::leak(args.excluded_folders.replace(" ", "").into_boxed_str());
-            for v in exstr.split(",") {
-                excluded_folders.0.insert(v);
-            }
-            let excluded = Arc::new(excluded_folders);
+            let excluded = excluded_folders(&args.excluded_folders);
 
             // pull origin
             if args.update_git {
@@ -242,7 +363,7 @@ fn main() {
                         while let Ok(handle) = rx.recv() {
                             let _ = handle.join();
                         }
-                        match build(tables, args.loption.as_str(), false) {
+                        match build(tables, &loptions, false, args.compact_lstrings) {
                             Err(e) => eprintln!(
                                 "{}",
                                 Red.bold().paint(format!("tables build failed: {}", e))
@@ -264,8 +385,9 @@ fn main() {
                     Ok(entity) => {
                         let generator = Generator {
                             entities: vec![entity],
-                            loption: &args.loption,
+                            loptions: &loptions,
                             lstring: true,
+                            compact_lstrings: false,
                         };
                         if let Err(e) = generator.build() {
                             eprintln!("{}", Red.bold().paint(format!("Build failed: {}", e)));
@@ -282,6 +404,50 @@ fn main() {
             println!("[End]");
             wait_for_user_input();
         }
+        args::Command::Graph { format, output } => {
+            let option = GraphFormat::from(format.as_str());
+            if let GraphFormat::Invalid = option {
+                eprintln!(
+                    "{}",
+                    Red.bold()
+                        .paint(format!("[Error]: Invalid graph format: {}", format))
+                );
+                exit(-1);
+            }
+            if let Err(e) = graph(excluded_folders(&args.excluded_folders), option, &output) {
+                eprintln!("{}", Red.bold().paint(format!("graph failed: {}", e)));
+                exit(-1);
+            }
+        }
+        args::Command::Lang { command } => {
+            let ret = match command {
+                args::LangCommand::Export { format, output } => {
+                    let option = TranslationFormat::from(format.as_str());
+                    if let TranslationFormat::Invalid = option {
+                        eprintln!(
+                            "{}",
+                            Red.bold()
+                                .paint(format!("[Error]: Invalid translation format: {}", format))
+                        );
+                        exit(-1);
+                    }
+                    lang_export(
+                        excluded_folders(&args.excluded_folders),
+                        option,
+                        &output,
+                        &loptions,
+                    )
+                }
+                args::LangCommand::Report { output } => {
+                    lang_report(excluded_folders(&args.excluded_folders), &output)
+                }
+                args::LangCommand::Import { files } => lang_import(&files),
+            };
+            if let Err(e) = ret {
+                eprintln!("{}", Red.bold().paint(format!("lang failed: {}", e)));
+                exit(-1);
+            }
+        }
         args::Command::Clean => {
             if let Err(e) = fs::remove_dir_all(unsafe { OUTPUT_SCRIPT_CODE_DIR }) {
                 eprintln!("{}", Red.bold().paint(format!("{}", e)));
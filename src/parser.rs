@@ -13,24 +13,29 @@ use vnlex::{
 };
 
 use crate::{
-    error,
+    config, error, escape,
     lex::{
         integer,
         states::{
             self,
             nodes::{
-                array_elements, array_type, assign, custom_type, enum_type, integer_literal,
-                list_type, literal_vals, tuple_type, tuple_type_elements, value_tuple_type,
-                value_type, values,
+                array_elements, array_type, assign, custom_type, dictionary_type, enum_type,
+                integer_literal, list_type, literal_vals, nullable_type, tuple_type,
+                tuple_type_elements, value_tuple_type, value_type, values,
             },
         },
         CData,
     },
+    schema,
     types::{
         custom::Custom,
-        numbers::{Bool, Byte, Decimal, Double, Float, Int, SByte, Short, UInt, UShort},
+        nullable::Nullable,
+        numbers::{
+            Bool, Byte, DateTime, Decimal, Double, Duration, Float, Int, Long, SByte, Short, UInt,
+            ULong, UShort,
+        },
         r#enum::Enum,
-        sequence::{Array, FixedArray, List, ShortList, Tuple, ValueTuple},
+        sequence::{Array, Dictionary, FixedArray, List, ShortList, Tuple, ValueTuple},
         string::{LString, SString},
         TypeInfo, Value,
     },
@@ -82,14 +87,24 @@ pub fn parse_assign_with_type(
 
     let vals = if vals.is_empty() {
         match tyinfo {
-            TypeInfo::Int | TypeInfo::SByte | TypeInfo::Short | TypeInfo::Bool => "-1",
+            TypeInfo::Int | TypeInfo::Long | TypeInfo::SByte | TypeInfo::Short | TypeInfo::Bool => {
+                "-1"
+            }
             TypeInfo::Array(_)
             | TypeInfo::Enum(_, _)
             | TypeInfo::List(_)
             | TypeInfo::FixedArray(_, _)
+            | TypeInfo::Dictionary(_, _)
+            | TypeInfo::Nullable(_)
             | TypeInfo::Custom(_) => "null",
             TypeInfo::Double | TypeInfo::Float => "0.0",
-            TypeInfo::Decimal | TypeInfo::UShort | TypeInfo::Uint | TypeInfo::Byte => "0",
+            TypeInfo::Decimal
+            | TypeInfo::UShort
+            | TypeInfo::Uint
+            | TypeInfo::ULong
+            | TypeInfo::Byte
+            | TypeInfo::Duration
+            | TypeInfo::DateTime => "0",
             _ => "",
         }
     } else if tyinfo == TypeInfo::String && vals.len() == 2 {
@@ -133,6 +148,10 @@ pub fn parse_assign(
 }
 
 pub fn parse_type(expr: &str, row: usize, col: usize) -> Result<Box<value_type>, error::Error> {
+    let expr = match config::expand_type_alias(expr)? {
+        Some(v) => v,
+        None => expr,
+    };
     let parser = &*PARSER;
     let mut cursor = Cursor::new(expr, row, col, None);
     let ty = parser
@@ -181,6 +200,12 @@ pub fn get_value_type(ty: &Box<value_type>) -> Result<TypeInfo, error::Error> {
         value_type::p16(v) => parse_tuple_type(v),
         value_type::p17(_) => Ok(TypeInfo::Byte),
         value_type::p18(_) => Ok(TypeInfo::SByte),
+        value_type::p19(v) => parse_dictionary_type(v),
+        value_type::p20(_) => Ok(TypeInfo::Long),
+        value_type::p21(_) => Ok(TypeInfo::ULong),
+        value_type::p22(v) => parse_nullable_type(v),
+        value_type::p23(_) => Ok(TypeInfo::Duration),
+        value_type::p24(_) => Ok(TypeInfo::DateTime),
     }
 }
 
@@ -216,6 +241,42 @@ fn parse_list_type(ty: &Box<list_type>) -> Result<TypeInfo, error::Error> {
     Ok(TypeInfo::List(Box::new(get_value_type(ty)?)))
 }
 
+fn parse_dictionary_type(ty: &Box<dictionary_type>) -> Result<TypeInfo, error::Error> {
+    let dictionary_type::p0(_, _, k, _, v, _) = ty.as_ref();
+    let key = get_value_type(k)?;
+    if key.is_lstring() {
+        return Err("LString can't be used as the key of Dictionary".into());
+    }
+    Ok(TypeInfo::Dictionary(
+        Box::new(key),
+        Box::new(get_value_type(v)?),
+    ))
+}
+
+fn parse_nullable_type(ty: &Box<nullable_type>) -> Result<TypeInfo, error::Error> {
+    let nullable_type::p0(ty, _) = ty.as_ref();
+    let inner = get_value_type(ty)?;
+    match inner {
+        TypeInfo::Int
+        | TypeInfo::Uint
+        | TypeInfo::Bool
+        | TypeInfo::Short
+        | TypeInfo::UShort
+        | TypeInfo::Float
+        | TypeInfo::Double
+        | TypeInfo::Decimal
+        | TypeInfo::Byte
+        | TypeInfo::SByte
+        | TypeInfo::Long
+        | TypeInfo::ULong
+        | TypeInfo::Duration
+        | TypeInfo::DateTime
+        | TypeInfo::Enum(_, _)
+        | TypeInfo::ValueTuple(_) => Ok(TypeInfo::Nullable(Box::new(inner))),
+        _ => Err(format!("`{}` is not a value type and can't be nullable", inner).into()),
+    }
+}
+
 fn parse_array_type(ty: &Box<array_type>) -> Result<TypeInfo, error::Error> {
     match ty.as_ref() {
         array_type::p0(ty, _, _) => Ok(TypeInfo::Array(Box::new(get_value_type(ty)?))),
@@ -281,11 +342,16 @@ fn get_value(
         value_type::p11(_) => parse_string_value(type_info, vals),
         value_type::p12(v) => parse_valuetuple_value(v, type_info, vals, ctx),
         value_type::p13(_) => parse_bool_value(type_info, vals),
-        value_type::p14(_) => parse_custom_value(type_info, vals),
+        value_type::p14(_) => parse_custom_value(type_info, vals, ctx),
         value_type::p15(_) => parse_enum_value(type_info, vals),
         value_type::p16(v) => parse_tuple_value(v, type_info, vals, ctx),
         value_type::p17(_) => parse_byte_value(type_info, vals),
         value_type::p18(_) => parse_sbyte_value(type_info, vals),
+        value_type::p19(v) => parse_dictionary_value(v, type_info, vals, ctx),
+        value_type::p20(_) => parse_long_value(type_info, vals),
+        value_type::p21(_) => parse_ulong_value(type_info, vals),
+        value_type::p22(v) => parse_nullable_value(v, type_info, vals, ctx),
+        value_type::p23(_) | value_type::p24(_) => parse_time_value(type_info, vals),
     }
 }
 
@@ -327,44 +393,57 @@ impl ConstValue<f64> for f64 {
 fn get_number_value<T: FromStr + Neg<Output = T> + ConstValue<T>>(
     val: &Box<integer_literal>,
 ) -> Result<T, error::Error> {
-    match val.as_ref() {
+    match get_suffixed_number_value(val)? {
+        (v, None) => Ok(v),
+        (_, Some(suffix)) => Err(format!("Unexpected suffix `{}` of number", suffix).into()),
+    }
+}
+
+/// the suffix of `@integer _ @ident` is returned as is, e.g. the unit of `500ms`
+fn get_suffixed_number_value<'a, T: FromStr + Neg<Output = T> + ConstValue<T>>(
+    val: &'a Box<integer_literal<'a>>,
+) -> Result<(T, Option<&'a str>), error::Error> {
+    let (v, suffix) = match val.as_ref() {
         states::nodes::integer_literal::p0(_) => todo!(),
         states::nodes::integer_literal::p1(_, _) => todo!(),
         states::nodes::integer_literal::p2(_) => todo!(),
         states::nodes::integer_literal::p3(_, _) => todo!(),
-        states::nodes::integer_literal::p4(v) => {
-            let (_, minus) = v
-                .as_ref()
-                .0
-                .data
-                .get_custom()
-                .ok_or::<error::Error>("get custom cdata failed".into())?
-                .into_digits()
-                .ok_or::<error::Error>("".into())?;
-            if minus {
-                Ok(-v.0.content.parse::<T>().map_err::<error::Error, _>(|_| {
-                    format!("parse number value failed: `-{}`", v.as_ref().0.content).into()
-                })?)
-            } else {
-                Ok(v.0.content.parse::<T>().map_err::<error::Error, _>(|_| {
-                    format!("parse number value failed: `{}`", v.as_ref().0.content).into()
-                })?)
-            }
-        }
-        states::nodes::integer_literal::p5(_, _) => todo!(),
+        states::nodes::integer_literal::p4(v) => (v, None),
+        states::nodes::integer_literal::p5(v, suffix) => (v, Some(suffix.as_ref().0.content)),
         states::nodes::integer_literal::p6(_) => todo!(),
         states::nodes::integer_literal::p7(_, _) => todo!(),
         states::nodes::integer_literal::p8(ty, _, val) => {
             if ty.as_ref().0.content == "short" {
                 match val.as_ref().0.content {
-                    "MinValue" => Ok(T::MIN),
+                    "MinValue" => return Ok((T::MIN, None)),
                     _ => unsafe { unreachable_unchecked() },
                 }
             } else {
                 unsafe { unreachable_unchecked() }
             }
         }
-    }
+    };
+    let (_, minus) = v
+        .as_ref()
+        .0
+        .data
+        .get_custom()
+        .ok_or::<error::Error>("get custom cdata failed".into())?
+        .into_digits()
+        .ok_or::<error::Error>("".into())?;
+    let val = if minus {
+        // parse with the sign so that `T::MIN` is still in range
+        format!("-{}", v.0.content)
+            .parse::<T>()
+            .map_err::<error::Error, _>(|_| {
+                format!("parse number value failed: `-{}`", v.as_ref().0.content).into()
+            })?
+    } else {
+        v.0.content.parse::<T>().map_err::<error::Error, _>(|_| {
+            format!("parse number value failed: `{}`", v.as_ref().0.content).into()
+        })?
+    };
+    Ok((val, suffix))
 }
 
 fn get_non_neg_integer_value<T: FromStr>(val: &Box<integer_literal>) -> Result<T, error::Error> {
@@ -401,7 +480,8 @@ fn parse_lstring_value(
     if raw.len() < 2 {
         return Err("raw string val is not long enough".into());
     }
-    let raw = &raw[1..raw.len() - 1];
+    let raw = escape::unquote(&raw[1..raw.len() - 1]);
+    let raw = raw.as_ref();
     let idx = {
         if raw.is_empty() {
             match ctx.ls_emptys.as_ref() {
@@ -523,10 +603,9 @@ fn parse_string_value(ty: TypeInfo, vals: &Box<values>) -> Result<Box<dyn Value>
             if raw.len() < 2 {
                 return Err("raw string val is not long enough".into());
             }
-            let raw = &raw[1..raw.len() - 1];
             Ok(Box::new(SString {
                 ty,
-                val: raw.into(),
+                val: escape::unquote(&raw[1..raw.len() - 1]).into_owned(),
                 is_null: false,
             }) as _)
         }
@@ -577,6 +656,208 @@ fn parse_uint_value(ty: TypeInfo, vals: &Box<values>) -> Result<Box<dyn Value>,
     Ok(Box::new(UInt { ty, val }) as _)
 }
 
+fn parse_long_value(ty: TypeInfo, vals: &Box<values>) -> Result<Box<dyn Value>, error::Error> {
+    let values::p0(literal_vals) = vals.as_ref() else {
+        return Err("expected literal vals when parse long value".into());
+    };
+
+    let literal_vals::p1(integer_vals) = literal_vals.as_ref() else {
+        return Err("expected integer vals when parse long value".into());
+    };
+
+    let val = get_number_value(integer_vals)?;
+    Ok(Box::new(Long { ty, val }) as _)
+}
+
+fn parse_ulong_value(ty: TypeInfo, vals: &Box<values>) -> Result<Box<dyn Value>, error::Error> {
+    let values::p0(literal_vals) = vals.as_ref() else {
+        return Err("expected literal vals when parse ulong value".into());
+    };
+
+    let literal_vals::p1(integer_vals) = literal_vals.as_ref() else {
+        return Err("expected integer vals when parse ulong value".into());
+    };
+
+    // the sign isn't a part of the content, reject it before the digits are parsed
+    if let states::nodes::integer_literal::p4(v) = integer_vals.as_ref() {
+        if v.as_ref()
+            .0
+            .data
+            .get_custom()
+            .and_then(|v| v.into_digits())
+            .is_some_and(|(_, minus)| minus)
+        {
+            return Err(format!(
+                "parse number value failed: `-{}` is out of the range of ulong",
+                v.as_ref().0.content
+            )
+            .into());
+        }
+    }
+    let val = get_non_neg_integer_value(integer_vals)?;
+    Ok(Box::new(ULong { ty, val }) as _)
+}
+
+fn parse_time_value(ty: TypeInfo, vals: &Box<values>) -> Result<Box<dyn Value>, error::Error> {
+    let values::p0(literal_vals) = vals.as_ref() else {
+        return Err(format!("expected literal vals when parse {} value", ty).into());
+    };
+
+    let text = match literal_vals.as_ref() {
+        // a number with a unit suffix, e.g. `500ms` or `1h30m`
+        literal_vals::p1(integer_vals) => match get_suffixed_number_value::<i64>(integer_vals)? {
+            (v, Some(unit)) => format!("{}{}", v, unit),
+            (v, None) => v.to_string(),
+        },
+        literal_vals::p2(float_vals) => match float_vals.as_ref() {
+            states::nodes::float_literal::p3(v1, _, v2, unit) => format!(
+                "{}.{}{}",
+                v1.as_ref().0.content,
+                v2.as_ref().0.content,
+                unit.as_ref().0.content
+            ),
+            _ => return Err(format!("Invalid literal vals for {}, missing a unit", ty).into()),
+        },
+        literal_vals::p3(raw) => {
+            let raw = raw.as_ref().0.content;
+            if raw.len() < 2 {
+                return Err("raw string val is not long enough".into());
+            }
+            escape::unquote(&raw[1..raw.len() - 1]).trim().to_string()
+        }
+        _ => return Err(format!("Invalid literal vals for {}", ty).into()),
+    };
+
+    let ms = match ty {
+        TypeInfo::Duration => parse_duration(&text)?,
+        _ if text
+            .trim_start_matches('-')
+            .bytes()
+            .all(|v| v.is_ascii_digit()) =>
+        {
+            // already a timestamp in the base unit
+            return Ok(Box::new(DateTime {
+                ty,
+                val: text.parse()?,
+            }) as _);
+        }
+        _ => parse_datetime(&text)?,
+    };
+    let val = if config::CFG.time_unit == "s" {
+        if ms % 1000 != 0 {
+            return Err(format!("`{}` is not a whole number of seconds", text).into());
+        }
+        ms / 1000
+    } else {
+        ms
+    };
+    match ty {
+        TypeInfo::Duration => Ok(Box::new(Duration { ty, val }) as _),
+        _ => Ok(Box::new(DateTime { ty, val }) as _),
+    }
+}
+
+/// `1h30m`, `500ms`, `1.5d`, a plain number is counted in the base unit, returns milliseconds
+fn parse_duration(text: &str) -> Result<i64, error::Error> {
+    let invalid = || -> error::Error { format!("Invalid duration `{}`", text).into() };
+    let trimmed = text.trim();
+    if let Ok(v) = trimmed.parse::<i64>() {
+        return Ok(if config::CFG.time_unit == "s" {
+            v.checked_mul(1000).ok_or_else(invalid)?
+        } else {
+            v
+        });
+    }
+
+    let (minus, mut rest) = match trimmed.strip_prefix('-') {
+        Some(v) => (true, v.trim_start()),
+        None => (false, trimmed),
+    };
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+    let mut total = 0f64;
+    while !rest.is_empty() {
+        let len = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .ok_or_else(invalid)?;
+        let num: f64 = rest[..len].parse().map_err(|_| invalid())?;
+        rest = &rest[len..];
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let unit = match &rest[..len] {
+            "d" => 86_400_000f64,
+            "h" => 3_600_000f64,
+            "m" | "min" => 60_000f64,
+            "s" => 1000f64,
+            "ms" => 1f64,
+            _ => return Err(invalid()),
+        };
+        total += num * unit;
+        rest = rest[len..].trim_start();
+    }
+    if total.fract() != 0.0 || total > i64::MAX as f64 {
+        return Err(invalid());
+    }
+    Ok(if minus { -(total as i64) } else { total as i64 })
+}
+
+/// `2024-05-01`, `2024/05/01 10:00` or `2024-05-01 10:00:30` in UTC, returns unix milliseconds
+fn parse_datetime(text: &str) -> Result<i64, error::Error> {
+    let invalid = || -> error::Error { format!("Invalid datetime `{}`", text).into() };
+    let text = text.trim();
+    let (date, time) = match text.split_once([' ', 'T']) {
+        Some((date, time)) => (date, time.trim()),
+        None => (text, ""),
+    };
+
+    let date = date
+        .split(['-', '/'])
+        .map(|v| v.parse::<i64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let [y, m, d] = date[..] else {
+        return Err(invalid());
+    };
+    let leap = (y % 4 == 0 && y % 100 != 0) || y % 400 == 0;
+    let days_of_month = match m {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return Err(invalid()),
+    };
+    if d < 1 || d > days_of_month {
+        return Err(invalid());
+    }
+
+    let time = if time.is_empty() {
+        vec![]
+    } else {
+        time.split(':')
+            .map(|v| v.parse::<i64>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    let (hh, mm, ss) = match time[..] {
+        [] => (0, 0, 0),
+        [hh, mm] => (hh, mm, 0),
+        [hh, mm, ss] => (hh, mm, ss),
+        _ => return Err(invalid()),
+    };
+    if !(0..24).contains(&hh) || !(0..60).contains(&mm) || !(0..60).contains(&ss) {
+        return Err(invalid());
+    }
+
+    // days since 1970-01-01 of the proleptic gregorian calendar
+    let (y, m) = if m <= 2 { (y - 1, m + 9) } else { (y, m - 3) };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * m + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    Ok(((days * 24 + hh) * 60 + mm) * 60_000 + ss * 1000)
+}
+
 fn parse_short_value(ty: TypeInfo, vals: &Box<values>) -> Result<Box<dyn Value>, error::Error> {
     let values::p0(literal_vals) = vals.as_ref() else {
         return Err("expected literal_vals".into());
@@ -744,6 +1025,76 @@ fn parse_array_value(
     }
 }
 
+fn parse_dictionary_value(
+    raw: &Box<dictionary_type>,
+    ty: TypeInfo,
+    vals: &Box<values>,
+    ctx: &Context,
+) -> Result<Box<dyn Value>, error::Error> {
+    if let values::p3(_) = vals.as_ref() {
+        return Ok(Box::new(Dictionary {
+            ty,
+            vals: Vec::new(),
+            is_null: true,
+        }) as _);
+    }
+
+    let dictionary_type::p0(_, _, kty, _, vty, _) = raw.as_ref();
+    let values::p1(array_vals) = vals.as_ref() else {
+        return Err("expected array_vals for Dictionary".into());
+    };
+    let mut entries = Vec::new();
+    match array_vals.as_ref() {
+        states::nodes::array_vals::p0(_, _) => {}
+        states::nodes::array_vals::p1(_, v, _) | states::nodes::array_vals::p2(_, v, _, _) => {
+            collect_array_elements(v, &mut entries);
+        }
+    }
+
+    let mut rvals = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let mut pair = Vec::with_capacity(2);
+        if let values::p1(entry) = entry.as_ref() {
+            match entry.as_ref() {
+                states::nodes::array_vals::p0(_, _) => {}
+                states::nodes::array_vals::p1(_, v, _)
+                | states::nodes::array_vals::p2(_, v, _, _) => {
+                    collect_array_elements(v, &mut pair);
+                }
+            }
+        }
+        if pair.len() != 2 {
+            return Err(format!(
+                "expected `{{key, value}}` for the entry of {}, found `{}`",
+                ty,
+                get_raw_value(entry)?
+            )
+            .into());
+        }
+        rvals.push((get_value(kty, pair[0], ctx)?, get_value(vty, pair[1], ctx)?));
+    }
+    Ok(Box::new(Dictionary {
+        ty,
+        vals: rvals,
+        is_null: false,
+    }) as _)
+}
+
+fn parse_nullable_value(
+    raw: &Box<nullable_type>,
+    ty: TypeInfo,
+    vals: &Box<values>,
+    ctx: &Context,
+) -> Result<Box<dyn Value>, error::Error> {
+    if let values::p3(_) = vals.as_ref() {
+        return Ok(Box::new(Nullable { ty, val: None }) as _);
+    }
+
+    let nullable_type::p0(raw, _) = raw.as_ref();
+    let val = get_value(raw, vals, ctx)?;
+    Ok(Box::new(Nullable { ty, val: Some(val) }) as _)
+}
+
 fn parse_valuetuple_value(
     raw: &Box<value_tuple_type>,
     ty: TypeInfo,
@@ -831,11 +1182,16 @@ fn parse_tuple_value_inner(
     Ok(())
 }
 
-fn parse_custom_value(ty: TypeInfo, vals: &Box<values>) -> Result<Box<dyn Value>, error::Error> {
+fn parse_custom_value(
+    ty: TypeInfo,
+    vals: &Box<values>,
+    ctx: &Context,
+) -> Result<Box<dyn Value>, error::Error> {
     if let values::p3(_) = vals.as_ref() {
         return Ok(Box::new(Custom {
             ty,
             args: Vec::new(),
+            fields: Vec::new(),
             is_null: true,
         }) as _);
     }
@@ -844,23 +1200,58 @@ fn parse_custom_value(ty: TypeInfo, vals: &Box<values>) -> Result<Box<dyn Value>
         return Err("expected array_vals for custom type".into());
     };
 
-    let mut args = Vec::new();
+    let mut elements = Vec::new();
     match array_vals.as_ref() {
-        states::nodes::array_vals::p0(_, _) => {
-            return Ok(Box::new(Custom {
-                ty,
-                args: Vec::new(),
-                is_null: false,
-            }) as _)
+        states::nodes::array_vals::p0(_, _) => {}
+        states::nodes::array_vals::p1(_, v, _) | states::nodes::array_vals::p2(_, v, _, _) => {
+            collect_array_elements(v, &mut elements);
         }
-        states::nodes::array_vals::p1(_, elements, _)
-        | states::nodes::array_vals::p2(_, elements, _, _) => {
-            parse_custom_value_inner(elements, &mut args)?;
+    }
+
+    let Some(schema) = schema::fields_of(&ty) else {
+        let mut args = Vec::new();
+        for v in elements {
+            args.push(get_raw_value(v)?);
         }
+        return Ok(Box::new(Custom {
+            ty,
+            args,
+            fields: Vec::new(),
+            is_null: false,
+        }) as _);
+    };
+
+    if elements.len() != schema.len() {
+        return Err(format!(
+            "custom type `{}` expects {} args ({}), found {}",
+            ty,
+            schema.len(),
+            schema
+                .iter()
+                .map(|v| format!("{} {}", v.ty, v.name))
+                .collect::<Vec<_>>()
+                .join(", "),
+            elements.len()
+        )
+        .into());
+    }
+
+    let mut fields = Vec::with_capacity(schema.len());
+    for (field, v) in schema.iter().zip(elements) {
+        let is_default = matches!(v.as_ref(), values::p1(e) if matches!(e.as_ref(), states::nodes::array_vals::p0(_, _)));
+        if is_default && !field.info.is_array_or_list() {
+            fields.push(None);
+            continue;
+        }
+        let fty = parse_type(field.ty, 0, 0)?;
+        let val = get_value(&fty, v, ctx)
+            .map_err(|e| format!("arg `{}` of custom type `{}`: {}", field.name, ty, e))?;
+        fields.push(Some(val));
     }
     Ok(Box::new(Custom {
         ty,
-        args,
+        args: Vec::new(),
+        fields,
         is_null: false,
     }) as _)
 }
@@ -946,20 +1337,17 @@ fn get_raw_literal_value(vals: &Box<literal_vals>) -> Result<String, error::Erro
     }
 }
 
-fn parse_custom_value_inner(
-    elements: &Box<array_elements>,
-    vals: &mut Vec<String>,
-) -> Result<(), error::Error> {
+fn collect_array_elements<'a, 'b>(
+    elements: &'a Box<array_elements<'b>>,
+    vals: &mut Vec<&'a Box<values<'b>>>,
+) {
     match elements.as_ref() {
-        array_elements::p0(v) => {
-            vals.push(get_raw_value(v)?);
-        }
+        array_elements::p0(v) => vals.push(v),
         array_elements::p1(prev, _, v) => {
-            parse_custom_value_inner(prev, vals)?;
-            vals.push(get_raw_value(v)?);
+            collect_array_elements(prev, vals);
+            vals.push(v);
         }
     }
-    Ok(())
 }
 
 fn parse_array_elements_value(
@@ -1041,17 +1429,38 @@ fn parse_double_value(ty: TypeInfo, vals: &Box<values>) -> Result<Box<dyn Value>
     Ok(Box::new(Double { ty, val }) as _)
 }
 
+/// an empty cell, `null` and `NULL` are the null of the nullable and reference types
+pub fn is_null(val: &str) -> bool {
+    matches!(val.trim(), "" | "null" | "NULL")
+}
+
 pub fn transfer_str_value(val: &str, ty: &TypeInfo) -> Result<String, error::Error> {
     let mut ret = String::new();
     match ty {
+        TypeInfo::Duration | TypeInfo::DateTime => {
+            // `1h30m` and `500ms` are numbers with a unit, `1h 30m` or `2024-05-01 10:00` are
+            // passed to the parser as strings
+            let tval = val.trim();
+            let unit = tval
+                .trim_start_matches('-')
+                .trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+            if tval.is_empty()
+                || (tval.len() >= 2 && tval.starts_with('"') && tval.ends_with('"'))
+                || (unit.len() < tval.trim_start_matches('-').len()
+                    && unit.chars().all(|c| c.is_ascii_alphanumeric()))
+            {
+                return Ok(tval.into());
+            }
+            return Ok(escape::quote(tval));
+        }
         TypeInfo::String | TypeInfo::LString | TypeInfo::Enum(_, _) => {
             let tval = val.trim();
-            if val.starts_with("\"") && val.ends_with("\"") {
-                return Ok(format!("{}", val));
-            } else if tval == "\"\"" {
+            if tval == "\"\"" {
                 return Ok(tval.into());
+            } else if tval.len() >= 2 && tval.starts_with("\"") && tval.ends_with("\"") {
+                return Ok(escape::quote(&tval[1..tval.len() - 1]));
             } else {
-                return Ok(format!("\"{}\"", val));
+                return Ok(escape::quote(val));
             }
         }
         TypeInfo::List(v) | TypeInfo::Array(v) | TypeInfo::FixedArray(v, _) => {
@@ -1061,7 +1470,7 @@ pub fn transfer_str_value(val: &str, ty: &TypeInfo) -> Result<String, error::Err
 
             ret.push('{');
             for s in util::split(val)? {
-                ret.push_str(transfer_str_value(s, v)?.as_str());
+                ret.push_str(transfer_str_value(s.trim(), v)?.as_str());
                 ret.push(',');
             }
         }
@@ -1084,6 +1493,68 @@ pub fn transfer_str_value(val: &str, ty: &TypeInfo) -> Result<String, error::Err
                 }
             }
         }
+        TypeInfo::Nullable(v) => {
+            if is_null(val) {
+                return Ok(val.into());
+            }
+            return transfer_str_value(val, v);
+        }
+        TypeInfo::Dictionary(k, v) => {
+            let trimmed = val.trim();
+            if is_null(val) {
+                return Ok(val.into());
+            }
+            if trimmed == "{}" {
+                return Ok(trimmed.into());
+            }
+
+            ret.push('{');
+            for entry in util::split(val)? {
+                let pair = util::split(entry)?;
+                if pair.len() != 2 {
+                    return Err(format!(
+                        "The entry `{}` of {} is not a `{{key, value}}` pair",
+                        entry.trim(),
+                        ty
+                    )
+                    .into());
+                }
+                ret.push('{');
+                ret.push_str(transfer_str_value(pair[0].trim(), k)?.as_str());
+                ret.push(',');
+                ret.push_str(transfer_str_value(pair[1].trim(), v)?.as_str());
+                ret.push_str("},");
+            }
+        }
+        TypeInfo::Custom(_) => {
+            let Some(fields) = schema::fields_of(ty) else {
+                return Ok(val.into());
+            };
+            let trimmed = val.trim();
+            if is_null(val) || trimmed == "{}" {
+                return Ok(val.into());
+            }
+
+            ret.push('{');
+            let subvals = util::split(val)?;
+            if subvals.len() != fields.len() {
+                return Err(format!(
+                    "The args of custom type `{}` expects {} args, found {}",
+                    ty,
+                    fields.len(),
+                    subvals.len()
+                )
+                .into());
+            }
+            for (field, v) in fields.iter().zip(subvals) {
+                if v.trim() == "{}" {
+                    ret.push_str(v);
+                } else {
+                    ret.push_str(transfer_str_value(v, &field.info)?.as_str());
+                }
+                ret.push(',');
+            }
+        }
         _ => {
             ret.push_str(val);
             return Ok(ret);
@@ -1106,3 +1577,159 @@ fn test() {
     let bval = parse_assign_with_type(&ty, &val, None, None).unwrap();
     let _ = bval.as_ref().code(&mut std::io::stdout());
 }
+
+#[test]
+fn test_long() {
+    let code = |ty: &str, val: &str| {
+        let ty = parse_type(ty, 0, 0).unwrap();
+        let mut code = String::new();
+        parse_assign_with_type(&ty, val, None, None)?.code_fmt(&mut code)?;
+        Ok::<_, error::Error>(code)
+    };
+    assert_eq!(code("long", "1700000000000").unwrap(), "1700000000000");
+    assert_eq!(
+        code("Int64", "-9223372036854775808").unwrap(),
+        "-9223372036854775808"
+    );
+    assert_eq!(
+        code("ulong", "18446744073709551615").unwrap(),
+        "18446744073709551615"
+    );
+    assert_eq!(
+        code("List<long>", "{1, 5000000000}").unwrap(),
+        "new List<long>{1,5000000000}"
+    );
+    assert_eq!(
+        code("ValueTuple<ulong, int>", "{5000000000, 1}").unwrap(),
+        "new ValueTuple<ulong,int>(5000000000,1)"
+    );
+    assert!(code("long", "9223372036854775808").is_err());
+    assert!(code("ulong", "-1").is_err());
+}
+
+#[test]
+fn test_nullable() {
+    let code = |ty: &str, val: &str| {
+        let ty = parse_type(ty, 0, 0).unwrap();
+        let mut code = String::new();
+        parse_assign_with_type(&ty, val, None, None)?.code_fmt(&mut code)?;
+        Ok::<_, error::Error>(code)
+    };
+    assert_eq!(code("int?", "").unwrap(), "null");
+    assert_eq!(code("int?", "-1").unwrap(), "-1");
+    assert_eq!(code("float?", "0").unwrap(), "0f");
+    assert_eq!(
+        code("List<int?>", "{1, null, 0}").unwrap(),
+        "new List<int?>{1,null,0}"
+    );
+    assert_eq!(
+        code("ValueTuple<bool?, short>", "{null, 2}").unwrap(),
+        "new ValueTuple<bool?,short>(null,2)"
+    );
+    assert!(code("string?", "").is_err());
+    assert!(code("int??", "").is_err());
+}
+
+#[test]
+fn test_dictionary() {
+    let ty = parse_type("Dictionary<int, string>", 0, 0).unwrap();
+    let val = transfer_str_value("{{1, a}, {2, \"b c\"}}", &get_value_type(&ty).unwrap()).unwrap();
+    let bval = parse_assign_with_type(&ty, &val, None, None).unwrap();
+    let mut code = String::new();
+    bval.code_fmt(&mut code).unwrap();
+    assert_eq!(code, "new Dictionary<int,string>{{1,\"a\"},{2,\"b c\"}}");
+
+    let bval = parse_assign_with_type(&ty, "", None, None).unwrap();
+    let mut code = String::new();
+    bval.code_fmt(&mut code).unwrap();
+    assert_eq!(code, "null");
+
+    let val = transfer_str_value(" NULL ", &get_value_type(&ty).unwrap()).unwrap();
+    let bval = parse_assign_with_type(&ty, &val, None, None).unwrap();
+    let mut code = String::new();
+    bval.code_fmt(&mut code).unwrap();
+    assert_eq!(code, "null");
+
+    assert!(parse_assign_with_type(&ty, "{{1, \"a\"}, {1, \"b\"}}", None, None).is_err());
+    assert!(parse_assign_with_type(&ty, "{{1, \"a\", 2}}", None, None).is_err());
+    assert!(parse_type("Dictionary<LString, int>", 0, 0)
+        .and_then(|v| get_value_type(&v))
+        .is_err());
+}
+
+#[test]
+fn test_time() {
+    let code = |ty: &str, val: &str| {
+        let ty = parse_type(ty, 0, 0).unwrap();
+        let tyinfo = get_value_type(&ty).unwrap();
+        let val = transfer_str_value(val, &tyinfo)?;
+        let mut code = String::new();
+        parse_assign_with_type(&ty, &val, None, None)?.code_fmt(&mut code)?;
+        Ok::<_, error::Error>(code)
+    };
+    assert_eq!(config::CFG.time_unit, "ms");
+    assert_eq!(
+        code("duration", "1h30m").unwrap(),
+        "System.TimeSpan.FromMilliseconds(5400000)"
+    );
+    assert_eq!(
+        code("duration", "500ms").unwrap(),
+        "System.TimeSpan.FromMilliseconds(500)"
+    );
+    assert_eq!(
+        code("duration[]", "{1.5s, 2d}").unwrap(),
+        "new System.TimeSpan[]{System.TimeSpan.FromMilliseconds(1500),System.TimeSpan.FromMilliseconds(172800000)}"
+    );
+    assert_eq!(
+        code("duration", "-1h").unwrap(),
+        "System.TimeSpan.FromMilliseconds(-3600000)"
+    );
+    assert_eq!(
+        code("duration?", "2m").unwrap(),
+        "System.TimeSpan.FromMilliseconds(120000)"
+    );
+    assert_eq!(
+        code("duration", "1h 30m").unwrap(),
+        "System.TimeSpan.FromMilliseconds(5400000)"
+    );
+    assert_eq!(
+        code("List<duration>", "{1h 30m, 500ms}").unwrap(),
+        "new List<System.TimeSpan>{System.TimeSpan.FromMilliseconds(5400000),System.TimeSpan.FromMilliseconds(500)}"
+    );
+    assert_eq!(
+        code("datetime", "2024-05-01 10:00").unwrap(),
+        "1714557600000"
+    );
+    assert_eq!(
+        code("datetime", "\"2024-05-01 10:00\"").unwrap(),
+        "1714557600000"
+    );
+    assert_eq!(code("DateTime", "1970/01/02").unwrap(), "86400000");
+    assert_eq!(code("datetime", "86400000").unwrap(), "86400000");
+    assert!(code("int", "500ms").is_err());
+    assert!(code("duration", "1x").is_err());
+    assert!(code("datetime", "2023-02-29").is_err());
+}
+
+#[test]
+fn test_string_escape() {
+    let code = |ty: &str, val: &str| {
+        let ty = parse_type(ty, 0, 0).unwrap();
+        let tyinfo = get_value_type(&ty).unwrap();
+        let val = transfer_str_value(val, &tyinfo)?;
+        let mut code = String::new();
+        parse_assign_with_type(&ty, &val, None, None)?.code_fmt(&mut code)?;
+        Ok::<_, error::Error>(code)
+    };
+    assert_eq!(
+        code("string", "say \"hi\" to {0}").unwrap(),
+        r#""say \"hi\" to {0}""#
+    );
+    assert_eq!(code("string", "line1\nline2").unwrap(), r#""line1\nline2""#);
+    assert_eq!(code("string", "C:\\a\\b").unwrap(), r#""C:\\a\\b""#);
+    assert_eq!(code("string", "\"quoted\"").unwrap(), r#""quoted""#);
+    assert_eq!(
+        code("string[]", "{\"a, b\", \"{c}\", d}").unwrap(),
+        r#"new string[]{"a, b","{c}","d"}"#
+    );
+}
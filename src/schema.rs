@@ -0,0 +1,115 @@
+use crate::{
+    config::{CFG, SOURCE_XLSXS_DIR},
+    error::Error,
+    parser::{get_value_type, parse_type},
+    types::TypeInfo,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock},
+};
+
+/// constructor argument of a custom type, in declaration order
+pub struct Field {
+    pub name: &'static str,
+    pub ty: &'static str,
+    pub info: TypeInfo,
+}
+
+fn schema_handler<P: AsRef<std::path::Path>>(
+    dir: P,
+) -> Result<HashMap<String, Vec<Field>>, Error> {
+    let mut ret = HashMap::new();
+    let mut path = dir.as_ref().to_path_buf();
+    path.push(CFG.custom_types_file);
+    if CFG.custom_types_file.is_empty() || !path.is_file() {
+        // without a schema custom types keep the raw args
+        return Ok(ret);
+    }
+
+    let tnl = std::fs::read_to_string(&path)?;
+    let tnl = Box::leak(Box::new(tnl));
+    let obj = tnl::ObjectAccessor(Box::leak(Box::new(
+        tnl::Object::try_from(tnl.as_str()).map_err(|e| format!("{:?}", e))?,
+    )));
+    let types = obj.attribute("custom_types")?.as_array()?;
+    for i in 0..types.0.elements.len() {
+        let ty = types.index(i)?.as_object()?;
+        let name = ty.attribute("name")?.as_str()?;
+        let arr = ty.attribute("fields")?.as_array()?;
+        let mut fields: Vec<Field> = Vec::new();
+        for j in 0..arr.0.elements.len() {
+            let field = arr.index(j)?.as_object()?;
+            let fname = field.attribute("name")?.as_str()?;
+            let fty = field.attribute("type")?.as_str()?;
+            if fields.iter().any(|v| v.name == fname) {
+                return Err(format!("Duplicate field `{}` of custom type `{}`", fname, name).into());
+            }
+            let info = get_value_type(&parse_type(fty, 0, 0)?).map_err(|e| {
+                format!(
+                    "Invalid type `{}` of field `{}` in custom type `{}`: {}",
+                    fty, fname, name, e
+                )
+            })?;
+            fields.push(Field {
+                name: fname,
+                ty: fty,
+                info,
+            });
+        }
+        if ret.insert(name.to_string(), fields).is_some() {
+            return Err(format!("Duplicate custom type `{}`", name).into());
+        }
+    }
+    Ok(ret)
+}
+
+pub static SCHEMA: LazyLock<Arc<HashMap<String, Vec<Field>>>> = LazyLock::new(|| {
+    match schema_handler(std::path::PathBuf::from(unsafe { SOURCE_XLSXS_DIR })) {
+        Ok(map) => Arc::new(map),
+        Err(e) => {
+            use ansi_term::Colour::Red;
+            eprintln!(
+                "{}",
+                Red.bold()
+                    .paint(format!("[Error]: Read custom type schema failed: {}", e))
+            );
+            std::process::exit(-1);
+        }
+    }
+});
+
+pub fn fields_of(ty: &TypeInfo) -> Option<&'static [Field]> {
+    match ty {
+        TypeInfo::Custom(name) => SCHEMA.get(name.as_str()).map(|v| v.as_slice()),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_schema_handler() {
+    let dir = std::env::temp_dir().join("custom_types_schema_test");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join(CFG.custom_types_file),
+        r#"custom_types: [
+    @type {
+        name: "Config.Reward"
+        fields: [
+            @field { name: "id" type: "int" }
+            @field { name: "name" type: "string" }
+            @field { name: "weights" type: "float[]" }
+        ]
+    }
+]"#,
+    )
+    .unwrap();
+    let schema = schema_handler(&dir).unwrap();
+    let fields = schema.get("Config.Reward").unwrap();
+    assert_eq!(
+        fields.iter().map(|v| v.name).collect::<Vec<_>>(),
+        vec!["id", "name", "weights"]
+    );
+    assert_eq!(fields[1].info, TypeInfo::String);
+    assert_eq!(fields[2].info, TypeInfo::Array(Box::new(TypeInfo::Float)));
+}
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use super::{BuildContext, ExcelTableWrapper, Sheet, TableCore, VectorView};
 use crate::{
-    config::{CFG, OUTPUT_SCRIPT_CODE_DIR, OUTPUT_SERVER_SCRIPT_CODE_DIR},
+    config::{self, CFG, OUTPUT_SCRIPT_CODE_DIR, OUTPUT_SERVER_SCRIPT_CODE_DIR},
     error::Error,
     types::Value,
     util::{self, conv_col_idx},
@@ -49,6 +49,10 @@ impl<'a> GlobalConfig<'a> {
                 let desc = cols.get_unchecked(3);
                 let modify = cols.get_unchecked(4);
                 let value = values.get_unchecked(idx);
+                // time types are spelled differently in C#, and a TimeSpan can't be a const
+                let cs_ty = util::replace_time_types(ty);
+                let has_time = cs_ty != *ty;
+                let ty = &cs_ty;
 
                 writeln!(file, "    /// <summary>")?;
                 writeln!(file, "    /// {}", desc)?;
@@ -58,7 +62,7 @@ impl<'a> GlobalConfig<'a> {
                     write!(file, "    public {} {} = ", ty, ident)?;
                     value.code(file)?;
                     writeln!(file, ";")?;
-                } else if value.ty_info().is_array_or_list() {
+                } else if value.ty_info().is_array_or_list() || has_time {
                     write!(file, "    public static readonly {} {} = ", ty, ident)?;
                     value.code(file)?;
                     writeln!(file, ";")?;
@@ -92,7 +96,7 @@ impl<'a> TableCore<'a> for GlobalConfig<'a> {
         &self.name
     }
 
-    fn build<'b: 'a>(&mut self, _: &'b BuildContext) -> Result<(), Error> {
+    fn build<'b: 'a>(&mut self, ctx: &'b BuildContext) -> Result<(), Error> {
         let mut client_stream = std::fs::File::create(format!(
             "{}/{}.{}",
             unsafe { OUTPUT_SCRIPT_CODE_DIR },
@@ -113,7 +117,7 @@ impl<'a> TableCore<'a> for GlobalConfig<'a> {
                 let value_ty = crate::parser::parse_type(*ty, 0, 0)?;
                 let tyinfo = crate::parser::get_value_type(&value_ty)?;
 
-                if tyinfo.contains_string_or_lstring_or_enum() {
+                if tyinfo.contains_string_or_lstring_or_enum() || tyinfo.contains_time() {
                     let tval = match crate::parser::transfer_str_value(val, &tyinfo) {
                         Ok(v) => v,
                         Err(e) => {
@@ -144,8 +148,26 @@ impl<'a> TableCore<'a> for GlobalConfig<'a> {
                         };
                     values.push(value);
                 } else {
+                    let val = if tyinfo.is_number() && crate::expr::is_expression(val, &ctx.consts)
+                    {
+                        match crate::expr::eval_literal(val, &tyinfo, &ctx.consts) {
+                            Ok(v) => v,
+                            Err(e) => {
+                                return Err(format!(
+                                    "In table {}, the Cell.({}, {}) {}",
+                                    self.name,
+                                    idx + 2,
+                                    conv_col_idx(3),
+                                    e
+                                )
+                                .into())
+                            }
+                        }
+                    } else {
+                        val.to_string()
+                    };
                     let value =
-                        match crate::parser::parse_assign_with_type(&value_ty, val, None, None) {
+                        match crate::parser::parse_assign_with_type(&value_ty, &val, None, None) {
                             Ok(e) => e,
                             Err(e) => {
                                 return Err(format!(
@@ -186,8 +208,7 @@ impl<'a> TableCore<'a> for GlobalConfig<'a> {
                 let mut row_data = Box::<[&str]>::new_uninit_slice(col);
                 for c in 0..col {
                     row_data[c].as_mut_ptr().write(
-                        table
-                            .cell_content(c, r)
+                        super::cell_text(table, c, r)
                             .ok_or::<Error>(
                                 format!(
                                     "Load table cell failed: table name = {}, in cell ({}, {})",
@@ -208,6 +229,22 @@ impl<'a> TableCore<'a> for GlobalConfig<'a> {
             raw.assume_init()
         };
 
+        // (ident, type, value, ..), the type aliases are expanded like the templates
+        let mut data = data;
+        if col > 2 {
+            for r in 1..row {
+                let ty = data[r].0[1];
+                if let Some(alias) = CFG.type_aliases.get(ty) {
+                    if data[r].0[2].is_empty() {
+                        data[r].0[2] = alias.default;
+                    }
+                }
+                if let Some(v) = config::expand_type_alias(ty)? {
+                    data[r].0[1] = v;
+                }
+            }
+        }
+
         Ok(Self {
             name,
             main: Sheet { col, row, data },
@@ -0,0 +1,377 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::{config::CFG, error::Error, escape, util};
+
+use super::TableEntity;
+
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+    Invalid,
+}
+
+impl From<&str> for GraphFormat {
+    fn from(value: &str) -> Self {
+        match value {
+            "dot" | "DOT" | "graphviz" => Self::Dot,
+            "mermaid" | "MERMAID" | "mmd" => Self::Mermaid,
+            _ => Self::Invalid,
+        }
+    }
+}
+
+/// table-to-table references declared by the `*Pattern` headers in `row_of_fk`
+#[derive(Default)]
+pub struct FkGraph {
+    nodes: BTreeSet<String>,
+    // (from, to) -> columns which create the edge
+    edges: BTreeMap<(String, String), BTreeSet<String>>,
+}
+
+impl FkGraph {
+    pub fn build(entities: &[TableEntity]) -> Result<Self, Error> {
+        let mut graph = FkGraph::default();
+
+        for entity in entities {
+            match entity {
+                TableEntity::Template(name, Some(sheet), _, _) => {
+                    graph.nodes.insert(name.clone());
+                    let height = super::Table::get_sheet_height(sheet, None)?;
+
+                    for c in 0..sheet.width() {
+                        let alias = sheet
+                            .cell_content(c, CFG.row_of_type)
+                            .and_then(|v| CFG.type_aliases.get(v.trim()));
+                        let pattern =
+                            match sheet.cell_content(c, CFG.row_of_fk).unwrap_or("").trim() {
+                                "" => alias.map_or("", |v| v.fk),
+                                v => v,
+                            };
+                        if !pattern.starts_with('*') {
+                            continue;
+                        }
+                        let pattern = &pattern[1..];
+                        let column = match sheet.cell_content(c, CFG.row_of_ident).map(|v| v.trim())
+                        {
+                            Some(v) if !v.is_empty() => v.to_string(),
+                            _ => util::conv_col_idx(c + 1),
+                        };
+                        let default = match sheet.cell_content(c, CFG.row_of_default).unwrap_or("")
+                        {
+                            v if v.trim().is_empty() => alias.map_or(v, |v| v.default),
+                            v => v,
+                        };
+                        let rows = (CFG.row_of_start..height).map(|r| {
+                            let v = sheet.cell_content(c, r).unwrap_or("");
+                            if v.trim().is_empty() {
+                                default
+                            } else {
+                                v
+                            }
+                        });
+
+                        let targets = fk_targets(pattern, std::iter::once(default).chain(rows))
+                            .map_err(|e| {
+                                format!(
+                                    "In table {}, collect fk targets of column `{}` failed: {}",
+                                    name, column, e
+                                )
+                            })?;
+                        for target in targets {
+                            graph
+                                .edges
+                                .entry((name.clone(), target))
+                                .or_default()
+                                .insert(column.clone());
+                        }
+                    }
+                }
+                TableEntity::Fk(name, _) => {
+                    graph.nodes.insert(name.clone());
+                }
+                _ => {}
+            }
+        }
+
+        for (_, to) in graph.edges.keys() {
+            graph.nodes.insert(to.clone());
+        }
+        Ok(graph)
+    }
+
+    pub fn self_references(&self) -> Vec<(&str, &BTreeSet<String>)> {
+        self.edges
+            .iter()
+            .filter(|((from, to), _)| from == to)
+            .map(|((from, _), cols)| (from.as_str(), cols))
+            .collect()
+    }
+
+    /// strongly connected components with more than one table, i.e. reference cycles
+    pub fn cycles(&self) -> Vec<Vec<&str>> {
+        let nodes = self.nodes.iter().map(|v| v.as_str()).collect::<Vec<_>>();
+        let index_of = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (*v, i))
+            .collect::<HashMap<_, _>>();
+        let mut adjacency = vec![Vec::new(); nodes.len()];
+        for (from, to) in self.edges.keys().filter(|(from, to)| from != to) {
+            adjacency[index_of[from.as_str()]].push(index_of[to.as_str()]);
+        }
+
+        let mut tarjan = Tarjan {
+            adjacency: &adjacency,
+            index: vec![None; nodes.len()],
+            lowlink: vec![0; nodes.len()],
+            on_stack: vec![false; nodes.len()],
+            stack: Vec::new(),
+            counter: 0,
+            components: Vec::new(),
+        };
+        for v in 0..nodes.len() {
+            if tarjan.index[v].is_none() {
+                tarjan.connect(v);
+            }
+        }
+
+        let mut ret = tarjan
+            .components
+            .into_iter()
+            .filter(|v| v.len() > 1)
+            .map(|v| {
+                let mut names = v.into_iter().map(|i| nodes[i]).collect::<Vec<_>>();
+                names.sort();
+                names
+            })
+            .collect::<Vec<_>>();
+        ret.sort();
+        ret
+    }
+
+    pub fn write_dot<W: std::io::Write + ?Sized>(&self, stream: &mut W) -> Result<(), Error> {
+        let cyclic = self.cyclic_nodes();
+        writeln!(stream, "digraph fk {{")?;
+        writeln!(stream, "{}rankdir=LR;", CFG.align_str)?;
+        for node in self.nodes.iter() {
+            if cyclic.contains(node.as_str()) {
+                writeln!(stream, "{}\"{}\" [color=red];", CFG.align_str, node)?;
+            } else {
+                writeln!(stream, "{}\"{}\";", CFG.align_str, node)?;
+            }
+        }
+        for ((from, to), cols) in self.edges.iter() {
+            write!(
+                stream,
+                "{}\"{}\" -> \"{}\" [label=\"{}\"",
+                CFG.align_str,
+                from,
+                to,
+                join(cols)
+            )?;
+            if from == to || (cyclic.contains(from.as_str()) && cyclic.contains(to.as_str())) {
+                write!(stream, ", color=red")?;
+            }
+            writeln!(stream, "];")?;
+        }
+        writeln!(stream, "}}")?;
+        Ok(())
+    }
+
+    pub fn write_mermaid<W: std::io::Write + ?Sized>(&self, stream: &mut W) -> Result<(), Error> {
+        let cyclic = self.cyclic_nodes();
+        writeln!(stream, "graph LR")?;
+        for node in self.nodes.iter() {
+            writeln!(stream, "{}{}[\"{}\"]", CFG.align_str, node, node)?;
+        }
+        for ((from, to), cols) in self.edges.iter() {
+            writeln!(
+                stream,
+                "{}{} -->|{}| {}",
+                CFG.align_str,
+                from,
+                join(cols),
+                to
+            )?;
+        }
+        if !cyclic.is_empty() {
+            writeln!(stream, "{}classDef cyclic stroke:#f00", CFG.align_str)?;
+            let cyclic = cyclic.into_iter().collect::<Vec<_>>();
+            writeln!(stream, "{}class {} cyclic", CFG.align_str, cyclic.join(","))?;
+        }
+        Ok(())
+    }
+
+    fn cyclic_nodes(&self) -> BTreeSet<&str> {
+        let mut ret = self.cycles().into_iter().flatten().collect::<BTreeSet<_>>();
+        for (v, _) in self.self_references() {
+            ret.insert(v);
+        }
+        ret
+    }
+}
+
+struct Tarjan<'a> {
+    adjacency: &'a [Vec<usize>],
+    index: Vec<Option<usize>>,
+    lowlink: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    counter: usize,
+    components: Vec<Vec<usize>>,
+}
+
+impl Tarjan<'_> {
+    fn connect(&mut self, v: usize) {
+        self.index[v] = Some(self.counter);
+        self.lowlink[v] = self.counter;
+        self.counter += 1;
+        self.stack.push(v);
+        self.on_stack[v] = true;
+
+        for &w in self.adjacency[v].iter() {
+            match self.index[w] {
+                None => {
+                    self.connect(w);
+                    self.lowlink[v] = std::cmp::min(self.lowlink[v], self.lowlink[w]);
+                }
+                Some(idx) if self.on_stack[w] => {
+                    self.lowlink[v] = std::cmp::min(self.lowlink[v], idx);
+                }
+                _ => {}
+            }
+        }
+
+        if Some(self.lowlink[v]) == self.index[v] {
+            let mut component = Vec::new();
+            while let Some(w) = self.stack.pop() {
+                self.on_stack[w] = false;
+                component.push(w);
+                if w == v {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+fn join(cols: &BTreeSet<String>) -> String {
+    cols.iter()
+        .map(|v| v.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Collect the referenced table names of a fk pattern (without the leading `*`),
+/// following the same rules as `FKValue::load_0/1/2`.
+fn fk_targets<'a, I: Iterator<Item = &'a str>>(
+    pattern: &str,
+    values: I,
+) -> Result<BTreeSet<String>, Error> {
+    let mut ret = BTreeSet::new();
+    let pat = pattern.chars().filter(|c| *c != ' ').collect::<String>();
+    let t1 = pattern.chars().all(|c| c.is_alphanumeric());
+    let t2 = pattern
+        .chars()
+        .filter(|c| *c != '{' && *c != '}')
+        .all(|c| c.is_alphanumeric());
+
+    if t1 || t2 {
+        ret.insert(
+            pattern
+                .chars()
+                .filter(|c| *c != '{' && *c != '}')
+                .collect::<String>(),
+        );
+    } else if pattern.contains('?') || pattern.contains('#') {
+        // the referenced tables are named by the `?` elements of each row
+        for val in values {
+            let rval = val.chars().filter(|c| *c != ' ').collect::<String>();
+            if rval.is_empty() || rval == "{}" {
+                continue;
+            }
+            dynamic_targets(&pat, &rval, &mut ret)?;
+        }
+    } else {
+        static_targets(&pat, &mut ret)?;
+    }
+    Ok(ret)
+}
+
+fn static_targets(pattern: &str, targets: &mut BTreeSet<String>) -> Result<(), Error> {
+    for pat in util::split(pattern)? {
+        if pat.contains('{') || pat.contains('}') {
+            static_targets(pat, targets)?;
+        } else if !pat.is_empty() {
+            targets.insert(pat.into());
+        }
+    }
+    Ok(())
+}
+
+fn dynamic_targets(
+    pattern: &str,
+    value: &str,
+    targets: &mut BTreeSet<String>,
+) -> Result<(), Error> {
+    let patterns = util::split(pattern)?;
+    let plen = patterns.len();
+    if plen == 0 {
+        return Ok(());
+    }
+
+    for (idx, v) in util::split(value)?.into_iter().enumerate() {
+        let pat = patterns[if idx < plen { idx } else { plen - 1 }];
+        if pat.contains('{') || pat.contains('}') {
+            dynamic_targets(pat, v, targets)?;
+        } else if pat.starts_with('?') {
+            let v = v.trim();
+            let v = match v.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+                Some(v) => escape::unquote(v),
+                None => v.into(),
+            };
+            if !v.is_empty() {
+                targets.insert(v.into_owned());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_fk_targets() {
+    let none = std::iter::empty();
+    assert_eq!(
+        fk_targets("Item", none)
+            .unwrap()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec!["Item"]
+    );
+    let none = std::iter::empty();
+    assert_eq!(
+        fk_targets("{Item,{,Skill}}", none)
+            .unwrap()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec!["Item", "Skill"]
+    );
+    let rows = ["{\"Item\",3}", "{}", "{\"Buff\", 7}"];
+    assert_eq!(
+        fk_targets("{?,#}", rows.into_iter())
+            .unwrap()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec!["Buff", "Item"]
+    );
+    // unquoted table names are kept whole
+    let rows = ["{剑x,1}", "{Skill,2}", "{\"\",3}"];
+    assert_eq!(
+        fk_targets("{?,#}", rows.into_iter())
+            .unwrap()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec!["Skill", "剑x"]
+    );
+}
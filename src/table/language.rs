@@ -1,42 +1,139 @@
-use std::{fs::File, io::Write, sync::Arc};
+use ansi_term::Colour::Yellow;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fs::File,
+    io::Write,
+    sync::Arc,
+};
 
 use crate::{
-    config::{CFG, LANG_OUTPUT_DIR, OUTPUT_SCRIPT_CODE_DIR},
+    config::{self, CFG, OUTPUT_SCRIPT_CODE_DIR},
     error::Error,
+    escape, util,
+};
+
+use super::{
+    markup, template::is_identifier, translation::SourceHashes, BuildContext, ExcelTableWrapper,
+    Sheet, TableCore,
 };
 
-use super::{BuildContext, ExcelTableWrapper, Sheet, TableCore};
+// members of LanguageKey, neither a key nor a nested class can have their names
+const RESERVED_KEYS: [&str; 4] = ["LanguageKey", "Invalid", "LanguageKeyToId", "_filedIdMap"];
 
 pub struct Languages<'a> {
     langs: Vec<(&'a str, Sheet<'a>)>,
 }
 
+impl Languages<'_> {
+    /// the column of `lang` is found by its name in the header rows, sheets without any
+    /// language name in the header keep the order of the configured languages after the key column
+    pub(super) fn column_of(sheet: &Sheet, lang: &str) -> Result<usize, Error> {
+        let mut named = false;
+        for row in 0..sheet.row.min(2) {
+            for col in 1..sheet.col {
+                let cell = sheet.cell(col, row, true)?;
+                if cell.eq_ignore_ascii_case(lang) {
+                    return Ok(col);
+                }
+                named |= CFG.languages.iter().any(|v| cell.eq_ignore_ascii_case(v));
+            }
+        }
+        match CFG.languages.iter().position(|v| *v == lang) {
+            Some(idx) if !named && idx + 1 < sheet.col => Ok(idx + 1),
+            _ => Err(format!("can't find the column of language `{}`", lang).into()),
+        }
+    }
+
+    /// a key is defined once across the sheets and the ids stay below `ushort.MaxValue` which
+    /// is `Invalid`, with `nested` the sheet names become class names too
+    fn check_keys(&self, nested: bool) -> Result<(), Error> {
+        let mut errors = Vec::new();
+        // key => (sheet, row, source text)
+        let mut defined = HashMap::new();
+        for (name, table) in self.langs.iter() {
+            if nested && (!is_identifier(name) || RESERVED_KEYS.contains(name)) {
+                errors.push(format!(
+                    "The sheet name `{}` of LString isn't a valid class name",
+                    name
+                ));
+            }
+            let source = Self::column_of(table, CFG.source_language()).ok();
+            for row in 2..table.row {
+                let key = table.cell(0, row, true)?;
+                if key.is_empty() {
+                    continue;
+                }
+                if RESERVED_KEYS.contains(&key) || (nested && key == *name) {
+                    errors.push(format!(
+                        "In the sheet {} of LString, Cell.({}, A): the key `{}` conflicts with a member of LanguageKey",
+                        name,
+                        row + 1,
+                        key
+                    ));
+                }
+                let text = match source {
+                    Some(col) => table.cell(col, row, false)?,
+                    None => "",
+                };
+                match defined.entry(key) {
+                    Entry::Vacant(e) => {
+                        e.insert((*name, row, text));
+                    }
+                    Entry::Occupied(e) => {
+                        let (sheet, r, t) = *e.get();
+                        errors.push(format!(
+                            "In the sheet {} of LString, Cell.({}, A): the key `{}` is already defined in Cell.({}, A) of the sheet {} with {} text",
+                            name,
+                            row + 1,
+                            key,
+                            r + 1,
+                            sheet,
+                            if t == text { "the same" } else { "a different" }
+                        ));
+                    }
+                }
+            }
+        }
+        if defined.len() > u16::MAX as usize {
+            errors.push(format!(
+                "LString has {} keys, the ids of at most {} fit in ushort",
+                defined.len(),
+                u16::MAX
+            ));
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n").into())
+        }
+    }
+}
+
 impl<'a> TableCore<'a> for Languages<'a> {
     fn name(&self) -> &str {
         "LanguageKey"
     }
 
     fn build<'b: 'a>(&mut self, ctx: &'b BuildContext) -> Result<(), Error> {
-        let path = format!(
-            "{}/{}.{}",
-            unsafe { LANG_OUTPUT_DIR },
-            CFG.language_file_name,
-            CFG.language_file_suffix
-        );
-        let mut langfile = File::create(path)?;
+        let nested = CFG.nested_language_keys;
+        self.check_keys(nested)?;
+
+        let mut langfiles = Vec::with_capacity(ctx.loptions.len());
+        for lang in ctx.loptions.iter() {
+            let path = format!(
+                "{}/{}.{}",
+                config::lang_output_dir(lang),
+                CFG.language_file_name,
+                CFG.language_file_suffix
+            );
+            langfiles.push((*lang, File::create(path)?));
+        }
         let mut file = File::create(format!(
             "{}/{}.{}",
             unsafe { OUTPUT_SCRIPT_CODE_DIR },
             self.name(),
             CFG.dest_code_suffix
         ))?;
-        let col = match ctx.loption {
-            "CN" => 1,
-            "CNH" => 2,
-            "EN" => 3,
-            "JP" => 4,
-            _ => unreachable!(),
-        };
 
         file.write_fmt(format_args!("{}{}", CFG.file_banner, CFG.line_end_flag))?;
         writeln!(file, "using System.Collections.Generic;")?;
@@ -47,24 +144,101 @@ impl<'a> TableCore<'a> for Languages<'a> {
         writeln!(file, "{{")?;
         writeln!(file, "#region const keys")?;
 
+        // (missing, stale, hashes) of every language, the source language is never stale
+        let mut checks = Vec::with_capacity(langfiles.len());
+        for (lang, _) in langfiles.iter() {
+            let hashes = if CFG.text_language(lang) == *lang && *lang != CFG.source_language() {
+                Some(SourceHashes::load(lang)?)
+            } else {
+                None
+            };
+            checks.push((0usize, 0usize, hashes));
+        }
+
+        // translations with broken placeholders or tags
+        let mut broken = Vec::new();
         let mut count = 0;
-        for (_, table) in self.langs.iter() {
+        for (name, table) in self.langs.iter() {
+            let source = Self::column_of(table, CFG.source_language())
+                .map_err(|e| format!("In the sheet {} of LString: {}", name, e))?;
+            let mut cols = Vec::with_capacity(langfiles.len());
+            for (lang, _) in langfiles.iter() {
+                cols.push(
+                    Self::column_of(table, CFG.text_language(lang))
+                        .map_err(|e| format!("In the sheet {} of LString: {}", name, e))?,
+                );
+            }
+            if nested {
+                writeln!(file, "    public static class {}", name)?;
+                writeln!(file, "    {{")?;
+            }
             for row in 2..table.row {
-                let v = table.cell(0, row, false)?;
-                if !v.trim().is_empty() {
-                    writeln!(
-                        file,
-                        "    public const ushort {} = {};",
-                        table.cell(0, row, false)?,
-                        count
-                    )?;
-                    count += 1;
+                // the line of a text is the id of its key, texts without key are dropped
+                let key = table.cell(0, row, true)?;
+                if key.is_empty() {
+                    continue;
                 }
-                let v = table.cell(col, row, false)?;
-                if !v.trim().is_empty() {
-                    writeln!(langfile, "{}", table.cell(col, row, false)?)?;
+                writeln!(
+                    file,
+                    "{}public const ushort {} = {};",
+                    if nested { "        " } else { "    " },
+                    key,
+                    count
+                )?;
+                count += 1;
+
+                // an imported translation of the current source text wins over the sheet
+                let context = format!("LString.{}", key);
+                let src = table.cell(source, row, false)?;
+                for (((lang, langfile), col), (missing, stale, hashes)) in
+                    langfiles.iter_mut().zip(cols.iter()).zip(checks.iter_mut())
+                {
+                    let location = |e| {
+                        format!(
+                            "In the sheet {} of LString, Cell.({}, {}) of {}: {}",
+                            name,
+                            row + 1,
+                            util::conv_col_idx(col + 1),
+                            lang,
+                            e
+                        )
+                    };
+                    let text_lang = CFG.text_language(lang);
+                    let v = match ctx
+                        .overlays
+                        .get(text_lang)
+                        .and_then(|o| o.get(&context, src))
+                    {
+                        Some(v) => v,
+                        None => {
+                            let v = table.cell(*col, row, false)?;
+                            if v.trim().is_empty() {
+                                *missing += !src.trim().is_empty() as usize;
+                            } else if let Some(hashes) = hashes {
+                                *stale += hashes.check(key, src, v) as usize;
+                            }
+                            v
+                        }
+                    };
+                    if text_lang != CFG.source_language() && !v.trim().is_empty() {
+                        if let Err(e) = markup::check(src, v) {
+                            broken.push(location(e));
+                        }
+                    }
+                    // an empty line keeps the following lines in step with the ids
+                    if v.trim().is_empty() {
+                        writeln!(langfile)?;
+                    } else if let Some(pseudo) = CFG.pseudo(lang) {
+                        let v = markup::pseudo(v, pseudo.expansion, pseudo.markers);
+                        writeln!(langfile, "{}", escape::language_txt(&v))?;
+                    } else {
+                        writeln!(langfile, "{}", escape::language_txt(v))?;
+                    }
                 }
             }
+            if nested {
+                writeln!(file, "    }}")?;
+            }
         }
 
         writeln!(file, "    public const ushort Invalid = ushort.MaxValue;")?;
@@ -87,10 +261,15 @@ impl<'a> TableCore<'a> for Languages<'a> {
         writeln!(file, "    private static readonly Dictionary<string,ushort> _filedIdMap = new Dictionary<string,ushort>()")?;
         writeln!(file, "    {{")?;
 
-        for (_, table) in self.langs.iter() {
+        for (name, table) in self.langs.iter() {
             for row in 2..table.row {
-                let v = table.cell(0, row, false)?;
-                if !v.trim().is_empty() {
+                let v = table.cell(0, row, true)?;
+                if v.is_empty() {
+                    continue;
+                }
+                if nested {
+                    writeln!(file, "        {{\"{}\", {}.{}}},", v, name, v)?;
+                } else {
                     writeln!(file, "        {{\"{}\", {}}},", v, v)?;
                 }
             }
@@ -99,7 +278,25 @@ impl<'a> TableCore<'a> for Languages<'a> {
         writeln!(file, "}}")?;
 
         file.flush()?;
-        langfile.flush()?;
+        for ((lang, langfile), (missing, stale, hashes)) in langfiles.iter_mut().zip(checks.iter())
+        {
+            langfile.flush()?;
+            if let Some(hashes) = hashes {
+                hashes.save()?;
+            }
+            if *missing > 0 || *stale > 0 {
+                eprintln!(
+                    "{}",
+                    Yellow.bold().paint(format!(
+                        "[Warning]: {} has {} missing and {} stale LString translations, see `lang report`",
+                        lang, missing, stale
+                    ))
+                );
+            }
+        }
+        if !broken.is_empty() {
+            return Err(broken.join("\n").into());
+        }
         Ok(())
     }
 
@@ -118,3 +315,87 @@ impl<'a> TableCore<'a> for Languages<'a> {
         Ok(Self { langs: data })
     }
 }
+
+#[test]
+fn test_column_of() {
+    use super::VectorView;
+    let sheet = |rows: &[&[&'static str]]| Sheet {
+        col: rows[0].len(),
+        row: rows.len(),
+        data: rows
+            .iter()
+            .map(|v| VectorView(v.to_vec().into_boxed_slice()))
+            .collect(),
+    };
+
+    let named = sheet(&[&["Key", "CN", "KO", "EN"], &["", "", "", ""]]);
+    assert_eq!(Languages::column_of(&named, "KO").unwrap(), 2);
+    assert_eq!(Languages::column_of(&named, "EN").unwrap(), 3);
+    assert!(Languages::column_of(&named, "TH").is_err());
+    // a named header never falls back to the position
+    let partial = sheet(&[&["Key", "CN", "KO", "EN", "X"], &["", "", "", "", ""]]);
+    assert!(Languages::column_of(&partial, "JP").is_err());
+
+    // legacy sheets without language names in the header
+    let legacy = sheet(&[
+        &["Key", "中文", "繁体", "英文", "日文"],
+        &["", "", "", "", ""],
+    ]);
+    assert_eq!(Languages::column_of(&legacy, "JP").unwrap(), 4);
+}
+
+#[test]
+fn test_check_keys() {
+    use super::VectorView;
+    let sheet = |rows: &[&[&'static str]]| Sheet {
+        col: rows[0].len(),
+        row: rows.len(),
+        data: rows
+            .iter()
+            .map(|v| VectorView(v.to_vec().into_boxed_slice()))
+            .collect(),
+    };
+    let langs = |sheets: Vec<(&'static str, Sheet<'static>)>| Languages { langs: sheets };
+    let source = CFG.source_language();
+
+    let ok = langs(vec![
+        (
+            "UI",
+            sheet(&[&["Key", source], &["", ""], &["Ok", "确定"], &["", ""]]),
+        ),
+        (
+            "Item",
+            sheet(&[&["Key", source], &["", ""], &["Sword", "剑"]]),
+        ),
+    ]);
+    assert!(ok.check_keys(false).is_ok());
+    assert!(ok.check_keys(true).is_ok());
+
+    let same = langs(vec![
+        ("UI", sheet(&[&["Key", source], &["", ""], &["Ok", "确定"]])),
+        (
+            "Dialog",
+            sheet(&[&["Key", source], &["", ""], &["Ok", "确定"]]),
+        ),
+    ]);
+    let e = same.check_keys(false).unwrap_err().to_string();
+    assert!(e.contains("`Ok` is already defined in Cell.(3, A) of the sheet UI with the same text"));
+
+    let conflict = langs(vec![(
+        "UI",
+        sheet(&[
+            &["Key", source],
+            &["", ""],
+            &["Ok", "确定"],
+            &["Ok", "好"],
+            &["Invalid", "无"],
+        ]),
+    )]);
+    let e = conflict.check_keys(false).unwrap_err().to_string();
+    assert!(e.contains("with a different text"));
+    assert!(e.contains("`Invalid` conflicts with a member of LanguageKey"));
+
+    let bad_name = langs(vec![("UI Text", sheet(&[&["Key", source], &["", ""]]))]);
+    assert!(bad_name.check_keys(false).is_ok());
+    assert!(bad_name.check_keys(true).is_err());
+}
@@ -0,0 +1,288 @@
+use std::io::Write;
+
+use crate::{
+    config::{CFG, OUTPUT_SCRIPT_CODE_DIR},
+    error::Error,
+    escape,
+};
+
+// the language file of a table is a json object, bump the version when its layout changes
+//
+// {
+//   "version": 1,
+//   "table": "Item",
+//   "texts": ["..", ..],                         // the text of an lstring id
+//   "extras": [{"key": "..", "value": ".."}, ..] // the extra_lang_sheets of the table
+// }
+
+pub(crate) const VERSION: u32 = 1;
+
+pub(crate) fn write<'t, W: Write + ?Sized>(
+    stream: &mut W,
+    table: &str,
+    texts: impl Iterator<Item = &'t str>,
+    extras: &[(&str, &str)],
+) -> Result<(), Error> {
+    writeln!(stream, "{{")?;
+    writeln!(stream, "  \"version\": {},", VERSION)?;
+    writeln!(stream, "  \"table\": {},", escape::json(table))?;
+    write!(stream, "  \"texts\": [")?;
+    for (i, v) in texts.enumerate() {
+        write!(
+            stream,
+            "{}\n    {}",
+            if i == 0 { "" } else { "," },
+            escape::json(v)
+        )?;
+    }
+    writeln!(stream, "\n  ],")?;
+    write!(stream, "  \"extras\": [")?;
+    for (i, (k, v)) in extras.iter().enumerate() {
+        write!(
+            stream,
+            "{}\n    {{\"key\": {}, \"value\": {}}}",
+            if i == 0 { "" } else { "," },
+            escape::json(k),
+            escape::json(v)
+        )?;
+    }
+    writeln!(stream, "\n  ]")?;
+    writeln!(stream, "}}")?;
+    Ok(())
+}
+
+/// LanguageFile.cs, the runtime reader of the language files
+pub(crate) fn write_loader() -> Result<(), Error> {
+    let mut file = std::fs::File::create(format!(
+        "{}/LanguageFile.{}",
+        unsafe { OUTPUT_SCRIPT_CODE_DIR },
+        CFG.dest_code_suffix
+    ))?;
+    file.write_fmt(format_args!("{}{}", CFG.file_banner, CFG.line_end_flag))?;
+    file.write_all(LOADER.replace("{VERSION}", &VERSION.to_string()).as_bytes())?;
+    file.flush()?;
+    Ok(())
+}
+
+const LOADER: &str = r##"using System;
+using System.Collections.Generic;
+using System.Globalization;
+using System.Text;
+
+namespace Config
+{
+    /// <summary>
+    /// 配置表的多语言文件
+    /// </summary>
+    public sealed class LanguageFile
+    {
+        public const int Version = {VERSION};
+
+        public string Table;
+        public string[] Texts;
+        public Dictionary<string, string> Extras;
+
+        public static LanguageFile Parse(string json)
+        {
+            var reader = new Reader(json);
+            var root = reader.ReadValue() as Dictionary<string, object>;
+            reader.SkipWhitespace();
+            if (root == null || reader.Position != json.Length)
+                throw new FormatException("Invalid language file");
+
+            var version = root.TryGetValue("version", out var v) ? Convert.ToInt32(v) : 0;
+            if (version != Version)
+                throw new FormatException($"Unsupported language file version {version}, expected {Version}");
+
+            var file = new LanguageFile
+            {
+                Table = root["table"] as string,
+                Extras = new Dictionary<string, string>(),
+            };
+            var texts = (List<object>)root["texts"];
+            file.Texts = new string[texts.Count];
+            for (var i = 0; i < texts.Count; i++)
+                file.Texts[i] = (string)texts[i];
+            foreach (var item in (List<object>)root["extras"])
+            {
+                var entry = (Dictionary<string, object>)item;
+                file.Extras[(string)entry["key"]] = (string)entry["value"];
+            }
+            return file;
+        }
+
+        private sealed class Reader
+        {
+            private readonly string _json;
+            public int Position;
+
+            public Reader(string json)
+            {
+                _json = json;
+            }
+
+            public void SkipWhitespace()
+            {
+                while (Position < _json.Length && char.IsWhiteSpace(_json[Position]))
+                    Position++;
+            }
+
+            private char Next()
+            {
+                if (Position >= _json.Length)
+                    throw new FormatException("Unexpected end of the language file");
+                return _json[Position++];
+            }
+
+            private void Expect(char c)
+            {
+                SkipWhitespace();
+                if (Next() != c)
+                    throw new FormatException($"Expected '{c}' at {Position - 1} of the language file");
+            }
+
+            public object ReadValue()
+            {
+                SkipWhitespace();
+                if (Position >= _json.Length)
+                    throw new FormatException("Unexpected end of the language file");
+                switch (_json[Position])
+                {
+                    case '{':
+                    {
+                        Position++;
+                        var obj = new Dictionary<string, object>();
+                        SkipWhitespace();
+                        if (Peek() == '}')
+                        {
+                            Position++;
+                            return obj;
+                        }
+                        while (true)
+                        {
+                            SkipWhitespace();
+                            var key = ReadString();
+                            Expect(':');
+                            obj[key] = ReadValue();
+                            if (EndOf('}'))
+                                return obj;
+                        }
+                    }
+                    case '[':
+                    {
+                        Position++;
+                        var list = new List<object>();
+                        SkipWhitespace();
+                        if (Peek() == ']')
+                        {
+                            Position++;
+                            return list;
+                        }
+                        while (true)
+                        {
+                            list.Add(ReadValue());
+                            if (EndOf(']'))
+                                return list;
+                        }
+                    }
+                    case '"':
+                        return ReadString();
+                    default:
+                    {
+                        var start = Position;
+                        while (Position < _json.Length && "+-.0123456789eE".IndexOf(_json[Position]) >= 0)
+                            Position++;
+                        if (start == Position)
+                            throw new FormatException($"Unexpected '{_json[Position]}' at {Position} of the language file");
+                        return double.Parse(_json.Substring(start, Position - start), CultureInfo.InvariantCulture);
+                    }
+                }
+            }
+
+            private char Peek()
+            {
+                return Position < _json.Length ? _json[Position] : '\0';
+            }
+
+            // `,` before the next element or the closing bracket
+            private bool EndOf(char closing)
+            {
+                SkipWhitespace();
+                var c = Next();
+                if (c == closing)
+                    return true;
+                if (c != ',')
+                    throw new FormatException($"Expected ',' or '{closing}' at {Position - 1} of the language file");
+                return false;
+            }
+
+            private string ReadString()
+            {
+                if (Next() != '"')
+                    throw new FormatException($"Expected a string at {Position - 1} of the language file");
+                var sb = new StringBuilder();
+                while (true)
+                {
+                    var c = Next();
+                    if (c == '"')
+                        return sb.ToString();
+                    if (c != '\\')
+                    {
+                        sb.Append(c);
+                        continue;
+                    }
+                    c = Next();
+                    switch (c)
+                    {
+                        case 'n': sb.Append('\n'); break;
+                        case 'r': sb.Append('\r'); break;
+                        case 't': sb.Append('\t'); break;
+                        case 'b': sb.Append('\b'); break;
+                        case 'f': sb.Append('\f'); break;
+                        case 'u':
+                            sb.Append((char)Convert.ToInt32(_json.Substring(Position, 4), 16));
+                            Position += 4;
+                            break;
+                        default: sb.Append(c); break;
+                    }
+                }
+            }
+        }
+    }
+}
+"##;
+
+#[test]
+fn test_write() {
+    let mut buf = Vec::new();
+    write(
+        &mut buf,
+        "Item",
+        ["长剑", "", "a=b\n\"c\""].into_iter(),
+        &[("k=1", "line1\nline2")],
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        r#"{
+  "version": 1,
+  "table": "Item",
+  "texts": [
+    "长剑",
+    "",
+    "a=b\n\"c\""
+  ],
+  "extras": [
+    {"key": "k=1", "value": "line1\nline2"}
+  ]
+}
+"#
+    );
+
+    let mut buf = Vec::new();
+    write(&mut buf, "Empty", std::iter::empty(), &[]).unwrap();
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        "{\n  \"version\": 1,\n  \"table\": \"Empty\",\n  \"texts\": [\n  ],\n  \"extras\": [\n  ]\n}\n"
+    );
+}
@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+
+// placeholders and rich-text tags of the texts, a translation must keep the ones of the source
+
+/// length of the `{0}`, `{name}`, `{0:N2}` of string.Format or the `%d`, `%1$s`, `%.2f` of printf
+/// at the start of `rest`
+fn placeholder_len(rest: &str) -> Option<usize> {
+    let bytes = rest.as_bytes();
+    match bytes.first()? {
+        b'{' => {
+            let end = rest.find('}')?;
+            (end > 1 && !rest[1..end].contains('{')).then_some(end + 1)
+        }
+        b'%' => {
+            let mut i = 1;
+            while i < bytes.len() && b"0123456789$-+#.".contains(&bytes[i]) {
+                i += 1;
+            }
+            (i < bytes.len() && b"diuxXfFeEgGsScp".contains(&bytes[i])).then_some(i + 1)
+        }
+        _ => None,
+    }
+}
+
+/// length of the text at the start of `rest` which must be kept as it is: placeholders, tags,
+/// `{{`, `%%` and the `\n` typed by the designers
+fn protected_len(rest: &str) -> Option<usize> {
+    if rest.starts_with("{{") || rest.starts_with("%%") {
+        return Some(2);
+    }
+    if let Some(escaped) = rest.strip_prefix('\\') {
+        return escaped.chars().next().map(|c| 1 + c.len_utf8());
+    }
+    if rest.starts_with('<') {
+        let end = rest.find('>')?;
+        let name = rest[1..end].trim_start_matches('/');
+        return name
+            .starts_with(|c: char| c.is_ascii_alphabetic())
+            .then_some(end + 1);
+    }
+    placeholder_len(rest)
+}
+
+fn placeholders(text: &str) -> BTreeMap<&str, usize> {
+    let mut ret = BTreeMap::new();
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        if text[i..].starts_with("{{") || text[i..].starts_with("%%") {
+            i += 2;
+        } else if let Some(len) = placeholder_len(&text[i..]) {
+            *ret.entry(&text[i..i + len]).or_default() += 1;
+            i += len;
+        } else {
+            i += c.len_utf8();
+        }
+    }
+    ret
+}
+
+/// names of the rich-text tags like `<color=#ff0000>`, `</b>` and `<sprite name=x/>`, an error
+/// if they aren't balanced, `<sprite>` and `<br>` never have a closing tag
+fn tags(text: &str) -> Result<BTreeMap<&str, usize>, String> {
+    let mut ret = BTreeMap::new();
+    let mut stack = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('>') else { break };
+        let raw = &rest[..end];
+        let (closing, body) = match raw.strip_prefix('/') {
+            Some(v) => (true, v),
+            None => (false, raw),
+        };
+        let name_len = body
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(body.len());
+        let name = &body[..name_len];
+        // `a < b` isn't a tag
+        if name.is_empty() || !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            continue;
+        }
+        rest = &rest[end + 1..];
+
+        if closing {
+            match stack.pop() {
+                Some(v) if v == name => {}
+                Some(v) => return Err(format!("`</{}>` closes `<{}>`", name, v)),
+                None => return Err(format!("`</{}>` without `<{}>`", name, name)),
+            }
+            continue;
+        }
+        *ret.entry(name).or_default() += 1;
+        if !body.ends_with('/') && !matches!(name, "sprite" | "br" | "page" | "space") {
+            stack.push(name);
+        }
+    }
+    match stack.pop() {
+        Some(v) => Err(format!("`<{}>` isn't closed", v)),
+        None => Ok(ret),
+    }
+}
+
+/// the translation must have the placeholders and the balanced tags of the source text
+pub(crate) fn check(source: &str, target: &str) -> Result<(), String> {
+    let (expected, found) = (placeholders(source), placeholders(target));
+    if expected != found {
+        return Err(format!(
+            "placeholders {:?} don't match {:?} of the source text",
+            found.keys().collect::<Vec<_>>(),
+            expected.keys().collect::<Vec<_>>()
+        ));
+    }
+    let found = tags(target)?;
+    // an unbalanced source text is reported in the source language itself
+    if let Ok(expected) = tags(source) {
+        if expected != found {
+            return Err(format!(
+                "tags {:?} don't match {:?} of the source text",
+                found.keys().collect::<Vec<_>>(),
+                expected.keys().collect::<Vec<_>>()
+            ));
+        }
+    }
+    Ok(())
+}
+
+const PSEUDO_UPPER: [char; 26] = [
+    'Å', 'Ɓ', 'Ç', 'Đ', 'É', 'Ƒ', 'Ĝ', 'Ĥ', 'Î', 'Ĵ', 'Ķ', 'Ĺ', 'Ṁ', 'Ñ', 'Ö', 'Þ', 'Ǫ', 'Ŕ', 'Š',
+    'Ţ', 'Û', 'Ṽ', 'Ŵ', 'Ẋ', 'Ý', 'Ž',
+];
+const PSEUDO_LOWER: [char; 26] = [
+    'å', 'ƀ', 'ç', 'đ', 'é', 'ƒ', 'ĝ', 'ĥ', 'î', 'ĵ', 'ķ', 'ĺ', 'ɱ', 'ñ', 'ö', 'þ', 'ǫ', 'ŕ', 'š',
+    'ţ', 'û', 'ṽ', 'ŵ', 'ẋ', 'ý', 'ž',
+];
+
+/// pseudo translation of `text`: accented letters, `expansion` times of the visible characters
+/// appended as padding, and wrapped by the markers, placeholders and tags are kept
+pub(crate) fn pseudo(text: &str, expansion: f32, markers: (&str, &str)) -> String {
+    let mut ret = String::with_capacity(text.len() * 2);
+    ret.push_str(markers.0);
+    let mut visible = 0;
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        if let Some(len) = protected_len(&text[i..]) {
+            ret.push_str(&text[i..i + len]);
+            i += len;
+            continue;
+        }
+        ret.push(match c {
+            'A'..='Z' => PSEUDO_UPPER[(c as u8 - b'A') as usize],
+            'a'..='z' => PSEUDO_LOWER[(c as u8 - b'a') as usize],
+            c => c,
+        });
+        if !c.is_whitespace() {
+            visible += 1;
+        }
+        i += c.len_utf8();
+    }
+    for _ in 0..(visible as f32 * expansion).ceil() as usize {
+        ret.push('~');
+    }
+    ret.push_str(markers.1);
+    ret
+}
+
+#[test]
+fn test_check() {
+    assert!(check("{0}获得{1}", "{0} gets {1}").is_ok());
+    assert!(check("{0}获得{1}", "{1} is given to {0}").is_ok());
+    assert!(check("{0}获得{1}", "{0} gets").is_err());
+    assert!(check("{0:N2}%", "{0:N0}%").is_err());
+    assert!(check("剩余%d次", "%d left").is_ok());
+    assert!(check("剩余%d次", "%s left").is_err());
+    // a `%` followed by a space is the percent sign of a number
+    assert!(check("暴击率提高20%", "20% chance to crit").is_ok());
+    assert!(check("伤害提高10%", "10% damage bonus").is_ok());
+    assert!(check("100%%", "{{100%%}}").is_ok());
+    assert!(check("<color=#ff0000>{0}</color>", "<color=red>{0}</color>").is_ok());
+    assert!(check("<color=#ff0000>{0}</color>", "<color=red>{0}").is_err());
+    assert!(check("<b>a</b>", "<b><i>a</b></i>").is_err());
+    assert!(check("<b>a</b>", "<i>a</i>").is_err());
+    assert!(check("<sprite=1>a < b", "<sprite=1> a < b").is_ok());
+    assert!(check("a\\nb", "a\\nb").is_ok());
+
+    let v = pseudo("Get {0} <color=red>Gold</color>\\n%d%%", 0.5, ("[", "]"));
+    assert_eq!(v, "[Ĝéţ {0} <color=red>Ĝöĺđ</color>\\n%d%%~~~~]");
+    assert!(check("Get {0} <color=red>Gold</color>\\n%d%%", &v).is_ok());
+    assert_eq!(pseudo("血量", 0.3, ("[", "]")), "[血量~]");
+}
@@ -1,6 +1,7 @@
 use crate::{
     config::{CFG, CONFIG_COLLECTION_PATH, MAGIC},
     error::Error,
+    expr::{self, Number},
     lex::states::nodes::value_type,
     types::Value,
     THREADS,
@@ -9,15 +10,21 @@ use ansi_term::Colour::Red;
 use dashmap::DashMap;
 use fk::FkTable;
 use global_config::GlobalConfig;
+pub use graph::{FkGraph, GraphFormat};
 use language::Languages;
 use std::{collections::HashMap, io::Write, ops::Deref, sync::Arc};
 use template::{Enums, Template};
-use xlsx_read::excel_table::ExcelTable;
+pub use translation::{Catalog, Overlay, Report, TranslationFormat};
+use xlsx_read::excel_table::{CellValue, ExcelTable};
 
 mod fk;
 mod global_config;
+mod graph;
 mod language;
+mod language_file;
+mod markup;
 mod template;
+mod translation;
 
 #[repr(transparent)]
 pub(crate) struct ExcelTableWrapper(pub(crate) ExcelTable);
@@ -71,9 +78,9 @@ pub enum TableEntity {
     Template(
         String,
         Option<ExcelTableWrapper>,
+        Vec<(String, ExcelTableWrapper, bool)>,
         Vec<(String, ExcelTableWrapper)>,
-        Vec<(String, ExcelTableWrapper)>,
-    ), // (name, template, enums, extras)
+    ), // (name, template, enums(name, sheet, is_flags), extras)
     GlobalConfig(String, Option<ExcelTableWrapper>),
     Language(Vec<(String, ExcelTableWrapper)>),
     Fk(String, ExcelTableWrapper),
@@ -143,11 +150,34 @@ impl TableEntity {
 
 pub struct Generator<'a> {
     pub entities: Vec<TableEntity>,
-    pub loption: &'a str,
+    pub loptions: &'a [&'static str],
     pub lstring: bool,
+    pub compact_lstrings: bool,
 }
 
 impl Generator<'_> {
+    fn load_constants(&self) -> Result<HashMap<String, Number>, Error> {
+        let Some((name, table)) = self.entities.iter().find_map(|v| match v {
+            TableEntity::GlobalConfig(name, Some(table)) => Some((name, table)),
+            _ => None,
+        }) else {
+            return Ok(HashMap::new());
+        };
+
+        // (ident, type, value, ..), the first row is the header
+        let cell = |c: usize, r: usize| table.cell_content(c, r).map(|v| v.trim()).unwrap_or("");
+        let mut rows = Vec::new();
+        for r in 1..table.height() {
+            let ty = crate::parser::parse_type(cell(1, r), 0, 0)
+                .and_then(|v| crate::parser::get_value_type(&v));
+            if ty.is_ok_and(|v| v.is_number()) {
+                rows.push((cell(0, r), cell(2, r)));
+            }
+        }
+        expr::load_constants(name, &rows)
+            .map_err(|e| format!("Load the constants of {} failed: {}", name, e).into())
+    }
+
     pub fn build(self) -> Result<(), Error> {
         if !self.lstring {
             // generate ConfigCollection.cs
@@ -240,11 +270,23 @@ namespace Config
                 .as_bytes(),
             )?;
             file.flush()?;
+
+            // reader of the language files of the tables
+            language_file::write_loader()?;
         }
 
         // loading tables
         let ctx = std::sync::Arc::new(BuildContext {
-            loption: self.loption,
+            loptions: self.loptions,
+            compact_lstrings: self.compact_lstrings,
+            consts: self.load_constants()?,
+            overlays: {
+                let mut overlays = HashMap::new();
+                for lang in self.loptions.iter().map(|v| CFG.text_language(v)) {
+                    overlays.insert(lang, Overlay::load(lang)?);
+                }
+                overlays
+            },
             ..Default::default()
         });
         let mut views = vec![];
@@ -283,10 +325,17 @@ namespace Config
 
 #[derive(Default)]
 pub struct BuildContext<'a> {
-    pub(crate) refs: DashMap<String, (HashMap<String, i32>, i32)>,
+    pub(crate) refs: DashMap<String, (HashMap<String, i64>, i64)>,
     // extra fk mappings
     pub(crate) efks: DashMap<String, HashMap<String, i32>>,
-    pub(crate) loption: &'a str,
+    // languages to build in this run
+    pub(crate) loptions: &'a [&'static str],
+    // drop the persisted lstring ids and assign them again
+    pub(crate) compact_lstrings: bool,
+    // numeric constants of GlobalConfig for the expressions in numeric cells
+    pub(crate) consts: HashMap<String, Number>,
+    // imported translations of the languages to build
+    pub(crate) overlays: HashMap<&'static str, Overlay>,
 }
 
 #[allow(dead_code)]
@@ -342,8 +391,8 @@ impl<'a> Table<'a> {
 
                 if !menums.is_empty() {
                     let mut enums = Enums::new(name.as_str());
-                    for (name, sheet) in menums.iter() {
-                        enums.load_enum(sheet, name.as_str())?;
+                    for (name, sheet, flags) in menums.iter() {
+                        enums.load_enum(sheet, name.as_str(), *flags)?;
                     }
                     enums.establish();
                     template.enums = Some(enums);
@@ -402,6 +451,15 @@ impl<'a> Table<'a> {
     }
 }
 
+/// the text of a cell, booleans are written as 1 and 0 in the sheets
+fn cell_text(table: &ExcelTable, x: usize, y: usize) -> Option<&str> {
+    match table.cell_value(x, y) {
+        Some(CellValue::Bool(true)) => Some("true"),
+        Some(CellValue::Bool(false)) => Some("false"),
+        _ => table.cell_content(x, y),
+    }
+}
+
 pub struct Sheet<'a> {
     col: usize,
     row: usize,
@@ -422,7 +480,7 @@ impl<'a> Sheet<'a> {
                 for c in 0..col {
                     row_data[c]
                         .as_mut_ptr()
-                        .write(table.cell_content(c, r).unwrap_or(""));
+                        .write(cell_text(table, c, r).unwrap_or(""));
                 }
                 raw[r]
                     .as_mut_ptr()
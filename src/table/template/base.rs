@@ -21,6 +21,43 @@ pub(crate) fn build<W: std::io::Write + ?Sized>(
     Ok(())
 }
 
+/// the lookups of `GetItem`, a `ulong` id can't be compared with the int `Count` and 64-bit ids
+/// must fit in the int keys of `_extraDataMap`
+fn write_id_lookups<W: std::io::Write + ?Sized>(
+    stream: &mut W,
+    tab_nums: i32,
+    id_type: &str,
+) -> Result<(), Error> {
+    let end = CFG.line_end_flag;
+    if id_type != "ulong" {
+        format(tab_nums, stream)?;
+        stream.write_all("if (id < 0) return null;".as_bytes())?;
+        stream.write_all(end.as_bytes())?;
+    }
+    format(tab_nums, stream)?;
+    if id_type == "ulong" {
+        stream.write_all(
+            "if (id < (ulong)_dataArray.Count) return _dataArray[(int)id];".as_bytes(),
+        )?;
+    } else {
+        stream.write_all("if (id < _dataArray.Count) return _dataArray[(int)id];".as_bytes())?;
+    }
+    stream.write_all(end.as_bytes())?;
+    format(tab_nums, stream)?;
+    if id_type == "long" || id_type == "ulong" {
+        stream.write_all(
+            "if (id <= int.MaxValue && _extraDataMap.TryGetValue((int) id, out var item)) return item;"
+                .as_bytes(),
+        )?;
+    } else {
+        stream.write_all(
+            "if (_extraDataMap.TryGetValue((int) id, out var item)) return item;".as_bytes(),
+        )?;
+    }
+    stream.write_all(end.as_bytes())?;
+    Ok(())
+}
+
 pub(crate) fn inner_build_client<W: std::io::Write + ?Sized>(
     template: &Template<'_>,
     stream: &mut W,
@@ -379,16 +416,13 @@ pub(crate) fn inner_build_client<W: std::io::Write + ?Sized>(
     format(tab_nums + 1, stream)?;
     stream.write("{".as_bytes())?;
     stream.write(end.as_bytes())?;
-    format(tab_nums + 2, stream)?;
-    stream.write("if (id < 0) return null;".as_bytes())?;
-    stream.write(end.as_bytes())?;
-    format(tab_nums + 2, stream)?;
-    stream.write("if (id < _dataArray.Count) return _dataArray[(int)id];".as_bytes())?;
-    stream.write(end.as_bytes())?;
-    format(tab_nums + 2, stream)?;
-    stream
-        .write("if (_extraDataMap.TryGetValue((int) id, out var item)) return item;".as_bytes())?;
-    stream.write(end.as_bytes())?;
+    write_id_lookups(
+        stream,
+        tab_nums + 2,
+        id_type
+            .as_ref()
+            .ok_or::<Error>(format!("Can't find id type for {} table", template.name).into())?,
+    )?;
     format(tab_nums + 2, stream)?;
     stream.write("// 预期为有效 Id 但仍然访问不到数据时".as_bytes())?;
     stream.write(end.as_bytes())?;
@@ -910,16 +944,13 @@ pub(crate) fn inner_build_server<W: std::io::Write + ?Sized>(
     format(tab_nums + 1, stream)?;
     stream.write("{".as_bytes())?;
     stream.write(end.as_bytes())?;
-    format(tab_nums + 2, stream)?;
-    stream.write("if (id < 0) return null;".as_bytes())?;
-    stream.write(end.as_bytes())?;
-    format(tab_nums + 2, stream)?;
-    stream.write("if (id < _dataArray.Count) return _dataArray[(int)id];".as_bytes())?;
-    stream.write(end.as_bytes())?;
-    format(tab_nums + 2, stream)?;
-    stream
-        .write("if (_extraDataMap.TryGetValue((int) id, out var item)) return item;".as_bytes())?;
-    stream.write(end.as_bytes())?;
+    write_id_lookups(
+        stream,
+        tab_nums + 2,
+        id_type
+            .as_ref()
+            .ok_or::<Error>(format!("Can't find id type for {} table", template.name).into())?,
+    )?;
     format(tab_nums + 2, stream)?;
     stream.write("// 预期为有效 Id 但仍然访问不到数据时".as_bytes())?;
     stream.write(end.as_bytes())?;
@@ -1240,3 +1271,29 @@ pub(crate) fn inner_build_server<W: std::io::Write + ?Sized>(
     stream.write("}".as_bytes())?;
     Ok(())
 }
+
+#[test]
+fn test_id_lookups() {
+    let lookups = |id_type: &str| {
+        let mut buf = Vec::new();
+        write_id_lookups(&mut buf, 0, id_type).unwrap();
+        String::from_utf8(buf).unwrap()
+    };
+
+    let int = lookups("int");
+    assert!(int.contains("if (id < 0) return null;"));
+    assert!(int.contains("if (id < _dataArray.Count) return"));
+    assert!(int.contains("if (_extraDataMap.TryGetValue((int) id"));
+
+    let long = lookups("long");
+    assert!(long.contains("if (id < 0) return null;"));
+    assert!(long.contains("if (id < _dataArray.Count) return"));
+    assert!(long.contains("if (id <= int.MaxValue && _extraDataMap.TryGetValue((int) id"));
+
+    // `ulong < int` doesn't compile and a ulong is never negative
+    let ulong = lookups("ulong");
+    assert!(!ulong.contains("id < 0"));
+    assert!(!ulong.contains("id < _dataArray.Count"));
+    assert!(ulong.contains("if (id < (ulong)_dataArray.Count) return _dataArray[(int)id];"));
+    assert!(ulong.contains("if (id <= int.MaxValue && _extraDataMap.TryGetValue((int) id"));
+}
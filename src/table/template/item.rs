@@ -26,22 +26,7 @@ pub(crate) fn inner_build_client<W: std::io::Write>(
     ctx: &InnerBuildContext<'_>,
 ) -> Result<(), Error> {
     let end = CFG.line_end_flag;
-    #[allow(unused_assignments)]
-    let mut count = 0;
     let base_name = format!("{}Item", template.name);
-    let comment = |content: &str, stream: &mut W| -> Result<(), Error> {
-        format(tab_nums + 1, stream)?;
-        stream.write("/// <summary>".as_bytes())?;
-        stream.write(end.as_bytes())?;
-        format(tab_nums + 1, stream)?;
-        stream.write("/// ".as_bytes())?;
-        stream.write(content.as_bytes())?;
-        stream.write(end.as_bytes())?;
-        format(tab_nums + 1, stream)?;
-        stream.write("/// </summary>".as_bytes())?;
-        stream.write(end.as_bytes())?;
-        Ok(())
-    };
 
     format(tab_nums, stream)?;
     stream.write("[Serializable]".as_bytes())?;
@@ -54,28 +39,7 @@ pub(crate) fn inner_build_client<W: std::io::Write>(
     stream.write("{".as_bytes())?;
     stream.write(end.as_bytes())?;
 
-    for item in ctx.items.iter() {
-        if !item.0.is_empty() {
-            comment(item.0, stream)?;
-        }
-
-        format(tab_nums + 1, stream)?;
-        stream.write("public readonly ".as_bytes())?;
-        let mut s = item.2.to_string();
-        convert_type(&mut s);
-
-        if s == "enum" {
-            stream.write_fmt(format_args!("E{}{}", template.name, item.1))?;
-        } else {
-            stream.write(replace_lstring(&s).as_bytes())?;
-        }
-
-        stream.write(" ".as_bytes())?;
-        stream.write(item.1.as_bytes())?;
-        stream.write(";".as_bytes())?;
-        stream.write(end.as_bytes())?;
-        stream.write(end.as_bytes())?;
-    }
+    write_fields(template, stream, tab_nums, ctx, true)?;
 
     // construct_0-------------
     format(tab_nums + 1, stream)?;
@@ -83,7 +47,7 @@ pub(crate) fn inner_build_client<W: std::io::Write>(
     stream.write(base_name.as_bytes())?;
     stream.write("(".as_bytes())?;
 
-    for item in ctx.items.iter() {
+    for (count, item) in ctx.items.iter().enumerate() {
         let rows = unsafe { ctx.values.get_unchecked(item.3) };
         if !rows.is_empty() {
             stream.write_fmt(format_args!("{}", rows[0].ty_info()))?;
@@ -93,7 +57,6 @@ pub(crate) fn inner_build_client<W: std::io::Write>(
         if count < ctx.items.len() - 1 {
             stream.write(",".as_bytes())?;
         }
-        count += 1;
     }
 
     stream.write(")".as_bytes())?;
@@ -110,34 +73,35 @@ pub(crate) fn inner_build_client<W: std::io::Write>(
     }
     // extra config----------
 
-    count = 0;
-    for (_, ident, _, col) in ctx.items.iter() {
-        if !ident.is_empty() {
-            format(tab_nums + 2, stream)?;
-            stream.write(ident.as_bytes())?;
-
-            let rows = unsafe { ctx.values.get_unchecked(*col) };
-            if !rows.is_empty() {
-                let info = rows[0].ty_info();
-                if info.is_lstring() {
-                    stream.write_fmt(format_args!(
-                        " = LocalStringManager.GetConfig(\"{}_language\", arg{})",
-                        template.name, count
-                    ))?;
-                } else if info.is_lstring_arr() {
-                    stream.write_fmt(format_args!(
-                        " = LocalStringManager.ConvertConfigList(\"{}_language\", arg{})",
-                        template.name, count
-                    ))?;
-                } else {
-                    stream.write_fmt(format_args!(" = arg{}", count))?;
-                }
-            }
+    let groups = collect_groups(&ctx.items)?;
+    for (idx, (_, ident, _, _)) in ctx.items.iter().enumerate() {
+        if ident.is_empty() {
+            continue;
+        }
+        let (group, members) = match ident.split_once('.') {
+            Some((group, _)) => match groups.iter().find(|v| v.0 == group) {
+                Some((_, members)) if members[0] == idx => (group, Some(members)),
+                _ => continue,
+            },
+            None => (*ident, None),
+        };
 
-            stream.write(";".as_bytes())?;
-            stream.write(end.as_bytes())?;
-            count += 1;
+        format(tab_nums + 2, stream)?;
+        stream.write(group.as_bytes())?;
+        match members {
+            Some(members) => {
+                let args = members
+                    .iter()
+                    .map(|v| arg_expr(template, ctx, *v))
+                    .collect::<Vec<_>>();
+                stream.write_fmt(format_args!(" = new {}Data({})", group, args.join(", ")))?;
+            }
+            None => {
+                stream.write_fmt(format_args!(" = {}", arg_expr(template, ctx, idx)))?;
+            }
         }
+        stream.write(";".as_bytes())?;
+        stream.write(end.as_bytes())?;
     }
 
     format(tab_nums + 1, stream)?;
@@ -157,33 +121,31 @@ pub(crate) fn inner_build_client<W: std::io::Write>(
     stream.write("{".as_bytes())?;
     stream.write(end.as_bytes())?;
 
-    count = 0;
-    for (_, ident, _, _) in ctx.items.iter() {
+    for (idx, (_, ident, _, _)) in ctx.items.iter().enumerate() {
+        let (group, members) = match ident.split_once('.') {
+            Some((group, _)) => match groups.iter().find(|v| v.0 == group) {
+                Some((_, members)) if members[0] == idx => (group, Some(members)),
+                _ => continue,
+            },
+            None => (*ident, None),
+        };
+
         format(tab_nums + 2, stream)?;
-        stream.write(ident.as_bytes())?;
-
-        if let Some((info, val)) = ctx.defaults.get(ident) {
-            stream.write(" = ".as_bytes())?;
-
-            if info.is_lstring() {
-                stream.write_fmt(format_args!(
-                    "LocalStringManager.GetConfig(\"{}_language\", default)",
-                    template.name
-                ))?;
-            } else if info.is_lstring_arr() {
-                stream.write_fmt(format_args!(
-                    "LocalStringManager.ConvertConfigList(\"{}_language\", default)",
-                    template.name
-                ))?;
-            } else {
-                unsafe { val.as_ref().unwrap_unchecked().code(stream) }?;
+        stream.write(group.as_bytes())?;
+        match members {
+            Some(members) => {
+                let mut vals = Vec::with_capacity(members.len());
+                for v in members.iter() {
+                    vals.push(default_expr(template, ctx, ctx.items[*v].1)?);
+                }
+                stream.write_fmt(format_args!(" = new {}Data({})", group, vals.join(", ")))?;
+            }
+            None => {
+                stream.write_fmt(format_args!(" = {}", default_expr(template, ctx, ident)?))?;
             }
-            stream.write(";".as_bytes())?;
-        } else {
-            stream.write(" = default;".as_bytes())?;
         }
+        stream.write(";".as_bytes())?;
         stream.write(end.as_bytes())?;
-        count += 1;
     }
 
     format(tab_nums + 1, stream)?;
@@ -242,19 +204,6 @@ pub(crate) fn inner_build_server<W: std::io::Write + ?Sized>(
     let end = CFG.line_end_flag;
     #[allow(unused_assignments)]
     let base_name = format!("{}Item", template.name);
-    let comment = |content: &str, stream: &mut W| -> Result<(), Error> {
-        format(tab_nums + 1, stream)?;
-        stream.write("/// <summary>".as_bytes())?;
-        stream.write(end.as_bytes())?;
-        format(tab_nums + 1, stream)?;
-        stream.write("/// ".as_bytes())?;
-        stream.write(content.as_bytes())?;
-        stream.write(end.as_bytes())?;
-        format(tab_nums + 1, stream)?;
-        stream.write("/// </summary>".as_bytes())?;
-        stream.write(end.as_bytes())?;
-        Ok(())
-    };
 
     format(tab_nums, stream)?;
     stream.write("[Serializable]".as_bytes())?;
@@ -267,28 +216,7 @@ pub(crate) fn inner_build_server<W: std::io::Write + ?Sized>(
     stream.write("{".as_bytes())?;
     stream.write(end.as_bytes())?;
 
-    for item in ctx.items.iter() {
-        if !item.0.is_empty() {
-            comment(item.0, stream)?;
-        }
-
-        format(tab_nums + 1, stream)?;
-        stream.write("public readonly ".as_bytes())?;
-        let mut s = item.2.to_string();
-        convert_type(&mut s);
-
-        if s == "enum" {
-            stream.write_fmt(format_args!("E{}{}", template.name, item.1))?;
-        } else {
-            stream.write(replace_lstring(&s).as_bytes())?;
-        }
-
-        stream.write(" ".as_bytes())?;
-        stream.write(item.1.as_bytes())?;
-        stream.write(";".as_bytes())?;
-        stream.write(end.as_bytes())?;
-        stream.write(end.as_bytes())?;
-    }
+    write_fields(template, stream, tab_nums, ctx, false)?;
 
     // enums
     for (k, arr) in ctx.enumflags.iter() {
@@ -332,6 +260,209 @@ pub(crate) fn inner_build_server<W: std::io::Write + ?Sized>(
     Ok(())
 }
 
+/// columns named `Group.Field` are gathered into a nested struct, returns the indices
+/// of the members in `ctx.items` per group, in the order of the first appearance
+fn collect_groups<'a>(
+    items: &[(&'a str, &'a str, &'a str, usize)],
+) -> Result<Vec<(&'a str, Vec<usize>)>, Error> {
+    let mut groups: Vec<(&str, Vec<usize>)> = Vec::new();
+    for (idx, (_, ident, ty, _)) in items.iter().enumerate() {
+        let Some((group, field)) = ident.split_once('.') else {
+            continue;
+        };
+        if group.is_empty() || field.is_empty() || field.contains('.') {
+            return Err(
+                format!("Invalid grouped ident `{}`, expected `Group.Field`", ident).into(),
+            );
+        }
+        // the enum of a column is named after its ident, which can't contain a `.`
+        if *ty == "enum" || *ty == "enum?" {
+            return Err(format!(
+                "Grouped ident `{}` can't be an enum column, move it out of the group `{}`",
+                ident, group
+            )
+            .into());
+        }
+        if items.iter().any(|v| v.1 == group) {
+            return Err(format!(
+                "Grouped ident `{}` conflicts with the column `{}`",
+                ident, group
+            )
+            .into());
+        }
+        match groups.iter_mut().find(|v| v.0 == group) {
+            Some((_, members)) => {
+                if members.iter().any(|v| items[*v].1 == *ident) {
+                    return Err(format!("Duplicate grouped ident `{}`", ident).into());
+                }
+                members.push(idx);
+            }
+            None => groups.push((group, vec![idx])),
+        }
+    }
+    Ok(groups)
+}
+
+fn field_type(template: &Template<'_>, item: &(&str, &str, &str, usize)) -> String {
+    let mut s = item.2.to_string();
+    convert_type(&mut s);
+
+    if s == "enum" {
+        format!("E{}{}", template.name, item.1)
+    } else if s == "enum?" {
+        format!("E{}{}?", template.name, item.1)
+    } else {
+        util::replace_time_types(&replace_lstring(&s))
+    }
+}
+
+fn write_fields<W: std::io::Write + ?Sized>(
+    template: &Template<'_>,
+    stream: &mut W,
+    tab_nums: i32,
+    ctx: &InnerBuildContext<'_>,
+    with_ctor: bool,
+) -> Result<(), Error> {
+    let end = CFG.line_end_flag;
+    let comment = |tab_nums: i32, content: &str, stream: &mut W| -> Result<(), Error> {
+        format(tab_nums, stream)?;
+        stream.write("/// <summary>".as_bytes())?;
+        stream.write(end.as_bytes())?;
+        format(tab_nums, stream)?;
+        stream.write("/// ".as_bytes())?;
+        stream.write(content.as_bytes())?;
+        stream.write(end.as_bytes())?;
+        format(tab_nums, stream)?;
+        stream.write("/// </summary>".as_bytes())?;
+        stream.write(end.as_bytes())?;
+        Ok(())
+    };
+    let groups = collect_groups(&ctx.items)?;
+
+    for (idx, item) in ctx.items.iter().enumerate() {
+        let Some((group, _)) = item.1.split_once('.') else {
+            if !item.0.is_empty() {
+                comment(tab_nums + 1, item.0, stream)?;
+            }
+            format(tab_nums + 1, stream)?;
+            stream.write_fmt(format_args!(
+                "public readonly {} {};",
+                field_type(template, item),
+                item.1
+            ))?;
+            stream.write(end.as_bytes())?;
+            stream.write(end.as_bytes())?;
+            continue;
+        };
+        let Some((_, members)) = groups.iter().find(|v| v.0 == group && v.1[0] == idx) else {
+            continue;
+        };
+
+        // nested struct
+        format(tab_nums + 1, stream)?;
+        stream.write("[Serializable]".as_bytes())?;
+        stream.write(end.as_bytes())?;
+        format(tab_nums + 1, stream)?;
+        stream.write_fmt(format_args!("public struct {}Data", group))?;
+        stream.write(end.as_bytes())?;
+        format(tab_nums + 1, stream)?;
+        stream.write("{".as_bytes())?;
+        stream.write(end.as_bytes())?;
+        for v in members.iter().map(|v| &ctx.items[*v]) {
+            if !v.0.is_empty() {
+                comment(tab_nums + 2, v.0, stream)?;
+            }
+            format(tab_nums + 2, stream)?;
+            stream.write_fmt(format_args!(
+                "public readonly {} {};",
+                field_type(template, v),
+                &v.1[group.len() + 1..]
+            ))?;
+            stream.write(end.as_bytes())?;
+            stream.write(end.as_bytes())?;
+        }
+        if with_ctor {
+            let params = members
+                .iter()
+                .map(|v| &ctx.items[*v])
+                .map(|v| format!("{} {}", field_type(template, v), &v.1[group.len() + 1..]))
+                .collect::<Vec<_>>();
+            format(tab_nums + 2, stream)?;
+            stream.write_fmt(format_args!("public {}Data({})", group, params.join(", ")))?;
+            stream.write(end.as_bytes())?;
+            format(tab_nums + 2, stream)?;
+            stream.write("{".as_bytes())?;
+            stream.write(end.as_bytes())?;
+            for v in members.iter().map(|v| &ctx.items[*v].1[group.len() + 1..]) {
+                format(tab_nums + 3, stream)?;
+                stream.write_fmt(format_args!("this.{} = {};", v, v))?;
+                stream.write(end.as_bytes())?;
+            }
+            format(tab_nums + 2, stream)?;
+            stream.write("}".as_bytes())?;
+            stream.write(end.as_bytes())?;
+        }
+        format(tab_nums + 1, stream)?;
+        stream.write("}".as_bytes())?;
+        stream.write(end.as_bytes())?;
+        stream.write(end.as_bytes())?;
+
+        format(tab_nums + 1, stream)?;
+        stream.write_fmt(format_args!("public readonly {}Data {};", group, group))?;
+        stream.write(end.as_bytes())?;
+        stream.write(end.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// the expression which converts the ctor arg of `ctx.items[idx]` to the field value
+fn arg_expr(template: &Template<'_>, ctx: &InnerBuildContext<'_>, idx: usize) -> String {
+    let rows = unsafe { ctx.values.get_unchecked(ctx.items[idx].3) };
+    match rows.first().map(|v| v.ty_info()) {
+        Some(info) if info.is_lstring() => format!(
+            "LocalStringManager.GetConfig(\"{}_language\", arg{})",
+            template.name, idx
+        ),
+        Some(info) if info.is_lstring_arr() => format!(
+            "LocalStringManager.ConvertConfigList(\"{}_language\", arg{})",
+            template.name, idx
+        ),
+        Some(info) if info.is_lstring_dict() => format!(
+            "arg{}?.ToDictionary(kv => kv.Key, kv => LocalStringManager.GetConfig(\"{}_language\", kv.Value))",
+            idx, template.name
+        ),
+        _ => format!("arg{}", idx),
+    }
+}
+
+fn default_expr(
+    template: &Template<'_>,
+    ctx: &InnerBuildContext<'_>,
+    ident: &str,
+) -> Result<String, Error> {
+    let Some((info, val)) = ctx.defaults.get(ident) else {
+        return Ok("default".into());
+    };
+
+    if info.is_lstring() {
+        Ok(format!(
+            "LocalStringManager.GetConfig(\"{}_language\", default)",
+            template.name
+        ))
+    } else if info.is_lstring_arr() {
+        Ok(format!(
+            "LocalStringManager.ConvertConfigList(\"{}_language\", default)",
+            template.name
+        ))
+    } else if info.is_lstring_dict() {
+        Ok("null".into())
+    } else {
+        let mut code = String::new();
+        unsafe { val.as_ref().unwrap_unchecked().code_fmt(&mut code) }?;
+        Ok(code)
+    }
+}
+
 fn convert_type(v: &mut String) {
     if let Some(idx) = v.find('[') {
         let mut n = idx;
@@ -376,3 +507,24 @@ fn replace_lstring(val: &str) -> String {
     }
     ret
 }
+
+#[test]
+fn test_collect_groups() {
+    let items = [
+        ("", "TemplateId", "int", 0),
+        ("", "Reward.ItemId", "int", 1),
+        ("", "Name", "string", 2),
+        ("", "Reward.Count", "int", 3),
+    ];
+    let groups = collect_groups(&items).unwrap();
+    assert_eq!(groups, vec![("Reward", vec![1, 3])]);
+
+    let items = [("", "Reward", "int", 0), ("", "Reward.Count", "int", 1)];
+    assert!(collect_groups(&items).is_err());
+    let items = [("", "Reward.Item.Id", "int", 0)];
+    assert!(collect_groups(&items).is_err());
+    let items = [("", "Reward.Kind", "enum", 0)];
+    assert!(collect_groups(&items).is_err());
+    let items = [("", "Reward.Kind", "enum?", 0)];
+    assert!(collect_groups(&items).is_err());
+}
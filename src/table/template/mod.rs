@@ -1,4 +1,5 @@
 use std::{
+    borrow::Cow,
     collections::{HashMap, HashSet},
     fmt::{Debug, Display},
     fs::File,
@@ -12,16 +13,19 @@ use xlsx_read::excel_table::ExcelTable;
 
 use crate::{
     config::{
-        CFG, ENUM_FLAGS_FILTER, LANG_OUTPUT_DIR, OUTPUT_ENUM_CODE_DIR, OUTPUT_SCRIPT_CODE_DIR,
+        self, CFG, ENUM_FLAGS_FILTER, OUTPUT_ENUM_CODE_DIR, OUTPUT_SCRIPT_CODE_DIR,
         OUTPUT_SERVER_ENUM_CODE_DIR, OUTPUT_SERVER_SCRIPT_CODE_DIR, REF_TEXT_DIR,
     },
     error::Error,
+    escape,
     types::{TypeInfo, Value},
     util::{self, conv_col_idx},
     THREADS,
 };
 
-use super::{BuildContext, ExcelTableWrapper, Sheet, Table, TableCore, VectorView};
+use super::{
+    language_file, markup, BuildContext, ExcelTableWrapper, Sheet, Table, TableCore, VectorView,
+};
 
 mod base;
 mod item;
@@ -32,7 +36,7 @@ pub struct Template<'a> {
     main: Sheet<'a>,
     fk_cols: Vec<usize>,
     extras: Vec<(&'a str, &'a str)>,
-    raw_refs: HashMap<String, i32>,
+    raw_refs: HashMap<String, i64>,
 }
 
 impl<'a> Template<'a> {
@@ -64,8 +68,49 @@ impl<'a> Template<'a> {
             raw.assume_init()
         };
 
+        // expand the type aliases, an alias also fills the empty fk pattern and default
+        let mut data = data;
+        if row > CFG.row_of_type.max(CFG.row_of_fk).max(CFG.row_of_default) {
+            for c in 0..col {
+                let ty = data[CFG.row_of_type].0[c].trim();
+                if let Some(alias) = CFG.type_aliases.get(ty) {
+                    if data[CFG.row_of_fk].0[c].trim().is_empty() {
+                        data[CFG.row_of_fk].0[c] = alias.fk;
+                    }
+                    if data[CFG.row_of_default].0[c].trim().is_empty() {
+                        data[CFG.row_of_default].0[c] = alias.default;
+                    }
+                }
+                if data[CFG.row_of_fk].0[c].trim().is_empty() {
+                    if let Some(alias) = config::nested_fk_alias(ty) {
+                        return Err(format!(
+                            "In table {}, Cell.({}, {}): the fk pattern of type alias `{}` can't \
+                             be applied inside `{}`, write the fk pattern in the column instead",
+                            name,
+                            CFG.row_of_type + 1,
+                            util::conv_col_idx(c + 1),
+                            alias,
+                            ty
+                        )
+                        .into());
+                    }
+                }
+                if let Some(v) = config::expand_type_alias(ty).map_err(|e| {
+                    format!(
+                        "In table {}, Cell.({}, {}): {}",
+                        name,
+                        CFG.row_of_type + 1,
+                        util::conv_col_idx(c + 1),
+                        e
+                    )
+                })? {
+                    data[CFG.row_of_type].0[c] = v;
+                }
+            }
+        }
+
         let (mut refs, mut max_ref_num, mut ref_file) = Self::load_refs(name)?;
-        let init = max_ref_num == CFG.ref_start_num - 1;
+        let init = max_ref_num == CFG.ref_start_num as i64 - 1;
 
         // extra None ref value
         if init {
@@ -150,14 +195,14 @@ impl<'a> Template<'a> {
         })
     }
 
-    fn load_refs(name: &str) -> Result<(HashMap<String, i32>, i32, File), Error> {
+    fn load_refs(name: &str) -> Result<(HashMap<String, i64>, i64, File), Error> {
         let path = format!(
             "{}/{}.{}",
             unsafe { REF_TEXT_DIR },
             name,
             CFG.ref_text_suffix,
         );
-        let mut max_num = CFG.ref_start_num - 1;
+        let mut max_num = CFG.ref_start_num as i64 - 1;
         let mut data = HashMap::new();
 
         match File::open(&path) {
@@ -209,116 +254,95 @@ impl<'a> Template<'a> {
 
     fn build_lstring_values(
         &self,
+        ctx: &BuildContext,
     ) -> Result<(HashMap<String, i32>, HashMap<(usize, usize), Vec<i32>>), Error> {
-        let mut seed = 0i32;
         let mut ls_map = HashMap::new();
         let mut emptys: HashMap<(usize, usize), Vec<i32>> = HashMap::new();
-        if !(0..self.main.col)
-            .map(|v| self.main.cell(v, CFG.row_of_type, true))
-            .any(|v| v.is_ok_and(|v| v.contains("LString") || v.contains("Lstring")))
-        {
+        // distinct texts in the order of their first appearance, the empty text included
+        let mut texts = Vec::new();
+        // the first cell of every text
+        let mut index = HashMap::new();
+        let mut empty_cells = Vec::new();
+        let found = visit_lstrings(&self.main, |pos, v| {
+            let v = v.unwrap_or_default();
+            if !index.contains_key(&v) {
+                texts.push(v.clone());
+                index.insert(v.clone(), pos);
+            }
+            if v.is_empty() {
+                empty_cells.push(pos);
+            }
+            Ok(())
+        })?;
+        if !found {
             return Ok((ls_map, emptys));
         }
-        let mut path = std::path::Path::new(unsafe { LANG_OUTPUT_DIR }).to_path_buf();
-        path.push(format!("{}_language", self.name));
-        path.set_extension("txt");
-        let mut file = File::create(path.as_path())?;
-
-        for r in CFG.row_of_start..self.main.row {
-            'col: for c in (0..self.main.col).filter(|v| {
-                self.main
-                    .cell(*v, CFG.row_of_type, true)
-                    .is_ok_and(|v| v.contains("Lstring") || v.contains("LString"))
-            }) {
-                let default = self.main.cell(c, CFG.row_of_default, true)?;
-                let ty = self.main.cell(c, CFG.row_of_type, true)?;
-                let val = {
-                    let v = self.main.cell(c, r, true)?;
-                    if v.is_empty() {
-                        default
-                    } else {
-                        v
-                    }
-                };
-                let pos = (c, r);
-                let trivial = { ty == "LString" || ty == "Lstring" };
-
-                if !trivial {
-                    let fval = val.chars().filter(|c| *c != ' ').collect::<String>();
-
-                    if fval.is_empty() || val == "{}" {
-                        continue 'col;
-                    }
 
-                    if !fval.starts_with('{') || !fval.ends_with('}') {
-                        return Err("Invalid format".into());
-                    }
-
-                    let val = &val[1..val.len() - 1];
-                    let raw_elements: Vec<&str> = val.split(',').collect();
-
-                    for v in raw_elements {
-                        if v.is_empty() {
-                            match emptys.entry(pos) {
-                                std::collections::hash_map::Entry::Occupied(mut e) => {
-                                    e.get_mut().push(seed);
-                                }
-                                std::collections::hash_map::Entry::Vacant(e) => {
-                                    let mut data = Vec::new();
-                                    data.push(seed);
-                                    e.insert(data);
-                                }
-                            }
-                            seed += 1;
-                            writeln!(file, "")?;
-                        } else {
-                            match ls_map.entry(v.into()) {
-                                std::collections::hash_map::Entry::Vacant(e) => {
-                                    writeln!(file, "{}", v)?;
-                                    e.insert(seed);
-                                    seed += 1;
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                } else {
-                    if val.is_empty() {
-                        match emptys.entry(pos) {
-                            std::collections::hash_map::Entry::Occupied(mut e) => {
-                                e.get_mut().push(seed);
-                            }
-                            std::collections::hash_map::Entry::Vacant(e) => {
-                                let mut data = Vec::new();
-                                data.push(seed);
-                                e.insert(data);
-                            }
-                        }
-                        seed += 1;
-                        writeln!(file, "")?;
-                    } else {
-                        match ls_map.entry(val.into()) {
-                            std::collections::hash_map::Entry::Vacant(e) => {
-                                e.insert(seed);
-                                seed += 1;
-                                writeln!(file, "{}", val)?;
-                            }
-                            _ => {}
-                        }
-                    }
+        let mut ids = if ctx.compact_lstrings {
+            LStringIds::empty(self.name)
+        } else {
+            LStringIds::load(self.name)?
+        };
+        let assigned = ids.assign(&texts.iter().map(|v| v.as_ref()).collect::<Vec<_>>());
+        ids.save()?;
+        for (v, id) in texts.iter().zip(assigned.iter()) {
+            if v.is_empty() {
+                for pos in empty_cells.iter() {
+                    emptys.entry(*pos).or_default().push(*id);
                 }
+            } else {
+                ls_map.insert(v.to_string(), *id);
             }
         }
+        // the freed ids are empty lines until they are reused
+        let mut lines = vec![""; assigned.iter().max().map_or(0, |v| *v as usize + 1)];
+        for (v, id) in texts.iter().zip(assigned.iter()) {
+            lines[*id as usize] = v.as_ref();
+        }
 
-        // extra language entrys
-        if !self.extras.is_empty() {
-            writeln!(file, ">>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>")?;
-            for (v1, v2) in self.extras.iter() {
-                writeln!(file, "{}={}", v1, v2)?;
+        for lang in ctx.loptions {
+            let overlay = ctx.overlays.get(CFG.text_language(lang));
+            let mut texts = Vec::with_capacity(lines.len());
+            for v in lines.iter() {
+                if v.is_empty() {
+                    texts.push(Cow::Borrowed(""));
+                    continue;
+                }
+                let translated = overlay.and_then(|o| o.get(self.name, v));
+                if let Some(Err(e)) = translated.map(|t| markup::check(v, t)) {
+                    let (c, r) = index[*v];
+                    return Err(format!(
+                        "In table {}, the {} translation of Cell.({}, {}): {}",
+                        self.name,
+                        lang,
+                        r + 1,
+                        conv_col_idx(c + 1),
+                        e
+                    )
+                    .into());
+                }
+                texts.push(match CFG.pseudo(lang) {
+                    Some(pseudo) => Cow::Owned(markup::pseudo(
+                        translated.unwrap_or(v),
+                        pseudo.expansion,
+                        pseudo.markers,
+                    )),
+                    None => Cow::Borrowed(translated.unwrap_or(v)),
+                });
             }
-        }
 
-        file.flush()?;
+            let mut path = std::path::PathBuf::from(config::lang_output_dir(lang));
+            path.push(format!("{}_language", self.name));
+            path.set_extension("json");
+            let mut file = std::io::BufWriter::new(File::create(path.as_path())?);
+            language_file::write(
+                &mut file,
+                self.name,
+                texts.iter().map(|v| v.as_ref()),
+                &self.extras,
+            )?;
+            file.flush()?;
+        }
         Ok((ls_map, emptys))
     }
 
@@ -386,7 +410,7 @@ impl<'a> TableCore<'a> for Template<'a> {
             }
         };
         // transfer lstrings
-        let (ls_map, emptys) = self.build_lstring_values()?;
+        let (ls_map, emptys) = self.build_lstring_values(ctx)?;
         let mut defkey = CFG.cell_of_defkey.0;
         let mut skip_cols = Vec::new();
         let mut required = Vec::new();
@@ -452,9 +476,12 @@ impl<'a> TableCore<'a> for Template<'a> {
             let ident = self.main.cell(c, CFG.row_of_ident, true)?;
             let ty = self.main.cell(c, CFG.row_of_type, true)?;
             let ety = format!("enum {}.{}", self.name, ident);
+            let nety = format!("{}?", ety);
             let value_ty = {
                 if ty == "enum" {
                     crate::parser::parse_type(ety.as_str(), 0, 0)?
+                } else if ty == "enum?" {
+                    crate::parser::parse_type(nety.as_str(), 0, 0)?
                 } else {
                     crate::parser::parse_type(ty, 0, 0)?
                 }
@@ -463,7 +490,7 @@ impl<'a> TableCore<'a> for Template<'a> {
             let enum_flag = self.main.cell(c, CFG.row_of_enum, true)?;
             let default = self.main.cell(c, CFG.row_of_default, true)?;
 
-            let get_value = |r: usize, idx: usize| -> Result<&str, Error> {
+            let get_value = |r: usize, idx: usize| -> Result<Cow<str>, Error> {
                 let val = self.main.cell(c, r, true)?;
                 let val = if val.is_empty() { default } else { val };
                 if self.fk_cols.contains(&c) {
@@ -480,15 +507,31 @@ impl<'a> TableCore<'a> for Template<'a> {
                             .into(),
                         )?
                         .value(idx)?;
-                    Ok(if fkv.is_empty() { val } else { fkv.as_str() })
+                    Ok(Cow::Borrowed(if fkv.is_empty() {
+                        val
+                    } else {
+                        fkv.as_str()
+                    }))
                 } else if ty == "enum" {
                     unsafe { self.enums.as_ref().unwrap_unchecked().get_value(ident, val) }
+                } else if ty == "enum?" {
+                    if val.is_empty() {
+                        Ok(Cow::Borrowed(val))
+                    } else {
+                        unsafe { self.enums.as_ref().unwrap_unchecked().get_value(ident, val) }
+                    }
                 } else if tyinfo.is_string() {
                     let val = self.main.cell(c, r, false)?;
                     let val = if val.is_empty() { default } else { val };
-                    Ok(val)
+                    Ok(Cow::Borrowed(val))
+                } else if tyinfo.is_number() && crate::expr::is_expression(val, &ctx.consts) {
+                    Ok(Cow::Owned(crate::expr::eval_literal(
+                        val,
+                        &tyinfo,
+                        &ctx.consts,
+                    )?))
                 } else {
-                    Ok(val)
+                    Ok(Cow::Borrowed(val))
                 }
             };
 
@@ -518,11 +561,13 @@ impl<'a> TableCore<'a> for Template<'a> {
                     std::collections::hash_map::Entry::Vacant(e) => {
                         let val = get_value(CFG.row_of_default, 0)
                             .map_err(|e| format!("In the table {}, {}", self.name, e))?;
+                        let val = val.as_ref();
 
-                        if tyinfo.is_lstring_or_lstringarr() {
+                        if tyinfo.contains_lstring() {
                             e.insert((tyinfo.clone(), None));
                         } else {
-                            if tyinfo.contains_string_or_lstring_or_enum() {
+                            if tyinfo.contains_string_or_lstring_or_enum() || tyinfo.contains_time()
+                            {
                                 let tval = match crate::parser::transfer_str_value(val, &tyinfo) {
                                     Ok(v) => v,
                                     Err(e) => return Err(format!(
@@ -583,8 +628,9 @@ impl<'a> TableCore<'a> for Template<'a> {
                 let pos = (c, r);
                 let val = get_value(r, r - CFG.row_of_start + 1)
                     .map_err(|e| format!("In the table {}, {}", self.name, e))?;
+                let val = val.as_ref();
 
-                if tyinfo.contains_string_or_lstring_or_enum() {
+                if tyinfo.contains_string_or_lstring_or_enum() || tyinfo.contains_time() {
                     let tval = match crate::parser::transfer_str_value(val, &tyinfo) {
                         Ok(v) => v,
                         Err(e) => {
@@ -677,6 +723,256 @@ impl<'a> TableCore<'a> for Template<'a> {
     }
 }
 
+/// persisted ids of the lstrings of a table, like the ref.txt of the template ids, so editing a
+/// row doesn't renumber the texts after it
+struct LStringIds {
+    path: String,
+    ids: HashMap<String, i32>,
+    dirty: bool,
+}
+
+impl LStringIds {
+    fn path(name: &str) -> String {
+        format!(
+            "{}/{}.lstring.{}",
+            unsafe { REF_TEXT_DIR },
+            name,
+            CFG.ref_text_suffix
+        )
+    }
+
+    /// without the persisted ids, i.e. the ids are compacted in the order of appearance
+    fn empty(name: &str) -> Self {
+        Self {
+            path: Self::path(name),
+            ids: HashMap::new(),
+            dirty: true,
+        }
+    }
+
+    fn load(name: &str) -> Result<Self, Error> {
+        let mut ret = Self::empty(name);
+        ret.dirty = false;
+        let content = match std::fs::read_to_string(&ret.path) {
+            Ok(v) => v,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(ret),
+            Err(e) => return Err(e.into()),
+        };
+        // quoted text and id in turn
+        let mut lines = content.lines().filter(|v| !v.trim().is_empty());
+        while let Some(text) = lines.next() {
+            let text = text.trim();
+            let id = lines
+                .next()
+                .ok_or::<Error>(format!("{}: lack of the id of {}", ret.path, text).into())?;
+            if text.len() < 2 || !text.starts_with('"') || !text.ends_with('"') {
+                return Err(format!("{}: expected a quoted text, found {}", ret.path, text).into());
+            }
+            ret.ids.insert(
+                escape::po_unquote(&text[1..text.len() - 1]).into(),
+                id.trim().parse()?,
+            );
+        }
+        Ok(ret)
+    }
+
+    /// the ids of `texts`, a known text keeps its id, a new one takes the lowest free id, the ids
+    /// of the texts which are gone are freed
+    fn assign(&mut self, texts: &[&str]) -> Vec<i32> {
+        let mut ret = vec![-1; texts.len()];
+        let mut taken = HashSet::new();
+        for (i, v) in texts.iter().enumerate() {
+            if let Some(id) = self.ids.get(*v) {
+                ret[i] = *id;
+                taken.insert(*id);
+            }
+        }
+        let mut next = 0;
+        for id in ret.iter_mut().filter(|v| **v < 0) {
+            while taken.contains(&next) {
+                next += 1;
+            }
+            *id = next;
+            taken.insert(next);
+        }
+
+        if self.ids.len() != texts.len() || texts.iter().any(|v| !self.ids.contains_key(*v)) {
+            self.dirty = true;
+            self.ids = texts
+                .iter()
+                .map(|v| v.to_string())
+                .zip(ret.iter().copied())
+                .collect();
+        }
+        ret
+    }
+
+    fn save(&self) -> Result<(), Error> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let mut ids = self.ids.iter().collect::<Vec<_>>();
+        ids.sort_by_key(|v| *v.1);
+        let mut file = std::io::BufWriter::new(File::create(&self.path)?);
+        for (text, id) in ids {
+            file.write_fmt(format_args!(
+                "{}{}{}{}",
+                escape::po(text),
+                CFG.line_end_flag,
+                id,
+                CFG.line_end_flag
+            ))?;
+        }
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// visit the texts of the LString cells in the data rows of `main`, `None` for an empty element,
+/// returns false if there is no LString column at all
+pub(crate) fn visit_lstrings<'s>(
+    main: &'s Sheet,
+    mut f: impl FnMut((usize, usize), Option<Cow<'s, str>>) -> Result<(), Error>,
+) -> Result<bool, Error> {
+    let mut cols = Vec::new();
+    for c in 0..main.col {
+        let ty = main.cell(c, CFG.row_of_type, true)?;
+        let ty = config::expand_type_alias(ty)?.unwrap_or(ty);
+        // the other columns report their broken types when the table is built
+        let Ok(tyinfo) =
+            crate::parser::parse_type(ty, 0, 0).and_then(|v| crate::parser::get_value_type(&v))
+        else {
+            continue;
+        };
+        if tyinfo.contains_lstring() {
+            cols.push((c, tyinfo));
+        }
+    }
+    if cols.is_empty() {
+        return Ok(false);
+    }
+
+    for r in CFG.row_of_start..main.row {
+        for (c, tyinfo) in cols.iter() {
+            let default = main.cell(*c, CFG.row_of_default, true)?;
+            let val = {
+                let v = main.cell(*c, r, true)?;
+                if v.is_empty() {
+                    default
+                } else {
+                    v
+                }
+            };
+            let pos = (*c, r);
+            visit_lstring_value(val, tyinfo, &mut |v| f(pos, v)).map_err(|e| {
+                format!(
+                    "Cell.({}, {}), invalid {} value `{}`: {}",
+                    r + 1,
+                    conv_col_idx(c + 1),
+                    tyinfo,
+                    val,
+                    e
+                )
+            })?;
+        }
+    }
+    Ok(true)
+}
+
+// the same walk as `parser::transfer_str_value`, so the texts are visited in the order the
+// parser takes the ids of the empty ones, and they are what the parser looks up
+fn visit_lstring_value<'s>(
+    val: &'s str,
+    ty: &TypeInfo,
+    f: &mut dyn FnMut(Option<Cow<'s, str>>) -> Result<(), Error>,
+) -> Result<(), Error> {
+    if !ty.contains_lstring() {
+        return Ok(());
+    }
+    let trimmed = val.trim();
+    match ty {
+        TypeInfo::LString => {
+            let v = if trimmed.len() >= 2 && trimmed.starts_with('"') && trimmed.ends_with('"') {
+                escape::unquote(&trimmed[1..trimmed.len() - 1])
+            } else {
+                Cow::Borrowed(val)
+            };
+            f(if v.is_empty() { None } else { Some(v) })
+        }
+        TypeInfo::List(v) | TypeInfo::Array(v) | TypeInfo::FixedArray(v, _) => {
+            if val.is_empty() {
+                return Ok(());
+            }
+            for s in util::split(val)? {
+                visit_lstring_value(s.trim(), v, f)?;
+            }
+            Ok(())
+        }
+        TypeInfo::Tuple(vs) | TypeInfo::ValueTuple(vs) => {
+            if val.is_empty() {
+                return Ok(());
+            }
+            let subvals = util::split(val)?;
+            if subvals.len() != vs.len() {
+                return Err("The subvals of Tuple are not match with generic type".into());
+            }
+            for (s, v) in subvals.into_iter().zip(vs.iter()) {
+                visit_lstring_value(s, v, f)?;
+            }
+            Ok(())
+        }
+        TypeInfo::Nullable(v) => {
+            if crate::parser::is_null(val) {
+                return Ok(());
+            }
+            visit_lstring_value(val, v, f)
+        }
+        TypeInfo::Dictionary(k, v) => {
+            if crate::parser::is_null(val) || trimmed == "{}" {
+                return Ok(());
+            }
+            for entry in util::split(val)? {
+                let pair = util::split(entry)?;
+                if pair.len() != 2 {
+                    return Err(format!(
+                        "The entry `{}` is not a `{{key, value}}` pair",
+                        entry.trim()
+                    )
+                    .into());
+                }
+                visit_lstring_value(pair[0].trim(), k, f)?;
+                visit_lstring_value(pair[1].trim(), v, f)?;
+            }
+            Ok(())
+        }
+        TypeInfo::Custom(_) => {
+            let Some(fields) = crate::schema::fields_of(ty) else {
+                return Ok(());
+            };
+            if crate::parser::is_null(val) || trimmed == "{}" {
+                return Ok(());
+            }
+            let subvals = util::split(val)?;
+            if subvals.len() != fields.len() {
+                return Err(format!(
+                    "The args of custom type `{}` expects {} args, found {}",
+                    ty,
+                    fields.len(),
+                    subvals.len()
+                )
+                .into());
+            }
+            for (field, v) in fields.iter().zip(subvals) {
+                if v.trim() != "{}" {
+                    visit_lstring_value(v, &field.info, f)?;
+                }
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
 pub(crate) struct InnerBuildContext<'a> {
     pub(crate) values: &'a [Vec<Box<dyn Value>>],
     pub(crate) nodefs: HashSet<&'a str>,
@@ -694,6 +990,7 @@ unsafe impl Sync for InnerBuildContext<'_> {}
 pub struct Enums<'a> {
     base: &'a str,
     mapping: Vec<(&'a str, HashMap<EnumValue, EnumValue>)>,
+    flags: HashSet<&'a str>,
 }
 
 struct EnumValue(*const u8, usize);
@@ -737,6 +1034,7 @@ impl<'a> Enums<'a> {
         Self {
             base,
             mapping: vec![],
+            flags: HashSet::new(),
         }
     }
 
@@ -745,25 +1043,64 @@ impl<'a> Enums<'a> {
         self.mapping.sort_by(|a, b| a.0.cmp(b.0));
     }
 
-    pub fn get_value(&self, name: &str, key: &str) -> Result<&str, Error> {
+    /// map the description to the enum ident, members of a flags enum can be combined as
+    /// `Fire|Ice` or `火|冰` and may be written by their idents too
+    pub fn get_value<'c>(&'c self, name: &str, key: &'c str) -> Result<Cow<'c, str>, Error> {
         match self.mapping.binary_search_by(|v| v.0.cmp(name)) {
             Ok(idx) => {
                 let mapping = unsafe { &self.mapping.get_unchecked(idx).1 };
-                unsafe {
-                    let meta = mapping
-                        .get(&EnumValue(key.as_ptr(), key.len()))
-                        .ok_or::<Error>(
-                            format!("can't find enum meta from mapping, key = {}", key).into(),
-                        )?;
-                    Ok(std::str::from_raw_parts(meta.0, meta.1))
+                let flags = self.flags.contains(name);
+                let lookup = |key: &str| -> Result<&'c str, Error> {
+                    unsafe {
+                        if let Some(meta) = mapping.get(&EnumValue(key.as_ptr(), key.len())) {
+                            return Ok(std::str::from_raw_parts(meta.0, meta.1));
+                        }
+                        mapping
+                            .values()
+                            .map(|v| std::str::from_raw_parts(v.0, v.1))
+                            .find(|v| flags && *v == key)
+                            .ok_or::<Error>(
+                                format!("can't find enum meta from mapping, key = {}", key).into(),
+                            )
+                    }
+                };
+
+                if !key.contains(['|', '｜']) {
+                    return Ok(Cow::Borrowed(lookup(key)?));
+                }
+                if !flags {
+                    return Err(format!(
+                        "`{}` combines the members of enum `{}` which isn't a flags enum",
+                        key, name
+                    )
+                    .into());
+                }
+                let mut idents = Vec::new();
+                for part in key.split(['|', '｜']).map(|v| v.trim()) {
+                    idents.push(lookup(part)?);
                 }
+                Ok(Cow::Owned(idents.join("|")))
             }
             _ => Err(format!("Can't find enum mapping for enum `{}`", name).into()),
         }
     }
 
-    pub fn load_enum<'b: 'a>(&mut self, table: &'b ExcelTable, name: &'b str) -> Result<(), Error> {
+    pub fn load_enum<'b: 'a>(
+        &mut self,
+        table: &'b ExcelTable,
+        name: &'b str,
+        flags: bool,
+    ) -> Result<(), Error> {
         let sheet = Self::inner_load_sheet(table)?;
+        Self::validate(&sheet, name, flags).map_err(|e| {
+            format!(
+                "Invalid enum sheet `{}_{}` of {}:\n{}",
+                if flags { "f" } else { "t" },
+                name,
+                self.base,
+                e
+            )
+        })?;
         self.save_to(
             &mut File::create(format!(
                 "{}/E{}{}.cs",
@@ -784,7 +1121,18 @@ impl<'a> Enums<'a> {
             &sheet,
             name,
         )?;
+        self.insert_mapping(&sheet, name, flags)
+    }
 
+    fn insert_mapping(
+        &mut self,
+        sheet: &Sheet<'a>,
+        name: &'a str,
+        flags: bool,
+    ) -> Result<(), Error> {
+        if flags {
+            self.flags.insert(name);
+        }
         let mut esmap = HashMap::new();
         for r in 0..sheet.row {
             let ident = sheet.cell(CFG.col_of_enum_ident, r, true)?;
@@ -798,6 +1146,99 @@ impl<'a> Enums<'a> {
         Ok(())
     }
 
+    /// reject sheets which would generate an enum that can't compile or can't be looked up
+    fn validate(sheet: &Sheet, name: &str, flags: bool) -> Result<(), Error> {
+        let mut errors = Vec::new();
+        if !is_identifier(name) {
+            errors.push(format!("`{}` is not a valid enum name", name));
+        }
+
+        let mut idents = HashMap::new();
+        let mut vals = HashMap::new();
+        let mut descs = HashMap::new();
+        for r in 0..sheet.row {
+            let ident = sheet.cell(CFG.col_of_enum_ident, r, true)?;
+            let val = sheet.cell(CFG.col_of_enum_val, r, true)?;
+            let desc = sheet.cell(CFG.col_of_enum_desc, r, true)?;
+            let pos = |c: usize| format!("Cell.({}, {})", r + 1, conv_col_idx(c + 1));
+
+            if ident == "Count" && !flags {
+                errors.push(format!(
+                    "{}, `Count` is reserved for the generated member",
+                    pos(CFG.col_of_enum_ident)
+                ));
+            } else if !is_identifier(ident) {
+                errors.push(format!(
+                    "{}, `{}` is not a valid identifier",
+                    pos(CFG.col_of_enum_ident),
+                    ident
+                ));
+            } else if let Some(prev) = idents.insert(ident, r) {
+                errors.push(format!(
+                    "{}, duplicate ident `{}`, first defined in row {}",
+                    pos(CFG.col_of_enum_ident),
+                    ident,
+                    prev + 1
+                ));
+            }
+
+            if val.is_empty() {
+                errors.push(format!("{}, missing value", pos(CFG.col_of_enum_val)));
+            } else if flags && !is_flags_value(val) {
+                errors.push(format!(
+                    "{}, value `{}` of a flags enum must be 0 or a power of two",
+                    pos(CFG.col_of_enum_val),
+                    val
+                ));
+            } else {
+                let key = match parse_enum_number(val) {
+                    Some(v) => v.to_string(),
+                    None => val.chars().filter(|c| !c.is_whitespace()).collect(),
+                };
+                if let Some(prev) = vals.insert(key, r) {
+                    errors.push(format!(
+                        "{}, duplicate value `{}`, first used in row {}",
+                        pos(CFG.col_of_enum_val),
+                        val,
+                        prev + 1
+                    ));
+                }
+            }
+
+            if !desc.is_empty() {
+                if let Some(prev) = descs.insert(desc, r) {
+                    errors.push(format!(
+                        "{}, duplicate description `{}`, first used in row {}",
+                        pos(CFG.col_of_enum_desc),
+                        desc,
+                        prev + 1
+                    ));
+                }
+            }
+        }
+
+        // the generated `Count` has no value of its own, it's the value of the last member + 1
+        if !flags && sheet.row > 0 {
+            let last = sheet.cell(CFG.col_of_enum_val, sheet.row - 1, true)?;
+            if let Some(count) = parse_enum_number(last).and_then(|v| v.checked_add(1)) {
+                if let Some(r) = vals.get(&count.to_string()) {
+                    errors.push(format!(
+                        "the generated `Count` = {} collides with `{}` in row {}, put the largest value last",
+                        count,
+                        sheet.cell(CFG.col_of_enum_ident, *r, true)?,
+                        r + 1
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n").into())
+        }
+    }
+
     fn save_to<W: std::io::Write + ?Sized>(
         &mut self,
         file: &mut W,
@@ -815,6 +1256,10 @@ impl<'a> Enums<'a> {
         ))?;
         file.write("/// </summary>".as_bytes())?;
         file.write(CFG.line_end_flag.as_bytes())?;
+        if self.flags.contains(name) {
+            file.write("[System.Flags]".as_bytes())?;
+            file.write(CFG.line_end_flag.as_bytes())?;
+        }
         file.write_fmt(format_args!(
             "public enum E{}{}{}",
             self.base, name, CFG.line_end_flag,
@@ -836,7 +1281,9 @@ impl<'a> Enums<'a> {
             ))?;
         }
 
-        file.write_fmt(format_args!("{}Count{}", '\t', CFG.line_end_flag))?;
+        if !self.flags.contains(name) {
+            file.write_fmt(format_args!("{}Count{}", '\t', CFG.line_end_flag))?;
+        }
         file.write("}".as_bytes())?;
         file.flush()?;
         Ok(())
@@ -883,6 +1330,108 @@ impl<'a> Enums<'a> {
     }
 }
 
+const CSHARP_KEYWORDS: &[&str] = &[
+    "abstract",
+    "as",
+    "base",
+    "bool",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "checked",
+    "class",
+    "const",
+    "continue",
+    "decimal",
+    "default",
+    "delegate",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "event",
+    "explicit",
+    "extern",
+    "false",
+    "finally",
+    "fixed",
+    "float",
+    "for",
+    "foreach",
+    "goto",
+    "if",
+    "implicit",
+    "in",
+    "int",
+    "interface",
+    "internal",
+    "is",
+    "lock",
+    "long",
+    "namespace",
+    "new",
+    "null",
+    "object",
+    "operator",
+    "out",
+    "override",
+    "params",
+    "private",
+    "protected",
+    "public",
+    "readonly",
+    "ref",
+    "return",
+    "sbyte",
+    "sealed",
+    "short",
+    "sizeof",
+    "stackalloc",
+    "static",
+    "string",
+    "struct",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "uint",
+    "ulong",
+    "unchecked",
+    "unsafe",
+    "ushort",
+    "using",
+    "virtual",
+    "void",
+    "volatile",
+    "while",
+];
+
+pub(crate) fn is_identifier(v: &str) -> bool {
+    let mut chars = v.chars();
+    match chars.next() {
+        Some(c) if c == '_' || c.is_alphabetic() => {}
+        _ => return false,
+    }
+    chars.all(|c| c == '_' || c.is_alphanumeric()) && !CSHARP_KEYWORDS.contains(&v)
+}
+
+/// numeric enum values written as decimal or hex, anything else is compared literally
+fn parse_enum_number(v: &str) -> Option<i64> {
+    let (neg, v) = match v.strip_prefix('-') {
+        Some(v) => (true, v.trim()),
+        None => (false, v),
+    };
+    let val = match v.strip_prefix("0x").or_else(|| v.strip_prefix("0X")) {
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None => v.parse().ok()?,
+    };
+    Some(if neg { -val } else { val })
+}
+
 #[derive(Default)]
 struct FKValue<'a> {
     newvals: HashMap<usize, VectorView<String>>,
@@ -1137,7 +1686,7 @@ impl<'a> FKValue<'a> {
     fn replace(
         st: &mut util::Stack<char>,
         dest: &mut String,
-        refs: Option<&dashmap::mapref::one::Ref<String, (HashMap<String, i32>, i32)>>,
+        refs: Option<&dashmap::mapref::one::Ref<String, (HashMap<String, i64>, i64)>>,
         mappings: Option<&dashmap::mapref::one::Ref<String, HashMap<String, i32>>>,
     ) -> Result<(), Error> {
         let mut s = String::with_capacity(10);
@@ -1169,3 +1718,180 @@ impl<'a> FKValue<'a> {
         Ok(())
     }
 }
+
+/// members of a flags enum must be 0 or a single bit
+fn is_flags_value(val: &str) -> bool {
+    parse_enum_number(val).is_some_and(|v| v >= 0 && v & (v - 1) == 0)
+}
+
+#[test]
+fn test_enum_names_and_values() {
+    assert!(is_identifier("Fire"));
+    assert!(is_identifier("_fire2"));
+    assert!(!is_identifier("2Fire"));
+    assert!(!is_identifier("Fire Ice"));
+    assert!(!is_identifier("class"));
+    assert!(!is_identifier(""));
+    assert_eq!(parse_enum_number("0x10"), Some(16));
+    assert_eq!(parse_enum_number("- 3"), Some(-3));
+    assert_eq!(parse_enum_number("1 << 2"), None);
+    assert!(is_flags_value("0"));
+    assert!(is_flags_value("0x40"));
+    assert!(!is_flags_value("3"));
+    assert!(!is_flags_value("-2"));
+}
+
+#[test]
+fn test_enum_flags() {
+    let sheet = |rows: &[[&'static str; 3]]| {
+        let mut data = Vec::new();
+        for row in rows {
+            let mut cells = vec![""; 3];
+            cells[CFG.col_of_enum_ident] = row[0];
+            cells[CFG.col_of_enum_val] = row[1];
+            cells[CFG.col_of_enum_desc] = row[2];
+            data.push(VectorView(cells.into_boxed_slice()));
+        }
+        Sheet {
+            col: 3,
+            row: data.len(),
+            data: data.into_boxed_slice(),
+        }
+    };
+    let tags = sheet(&[
+        ["Fire", "0x1", "火"],
+        ["Ice", "0x2", "冰"],
+        ["Wind", "0x4", "风"],
+    ]);
+    let kinds = sheet(&[["Melee", "0", "近战"], ["Ranged", "1", "远程"]]);
+    let mut enums = Enums::new("Skill");
+    enums.insert_mapping(&tags, "Tag", true).unwrap();
+    enums.insert_mapping(&kinds, "Kind", false).unwrap();
+    enums.establish();
+
+    assert_eq!(enums.get_value("Tag", "火").unwrap(), "Fire");
+    assert_eq!(enums.get_value("Tag", "火|冰").unwrap(), "Fire|Ice");
+    assert_eq!(enums.get_value("Tag", "Fire | 风").unwrap(), "Fire|Wind");
+    assert_eq!(enums.get_value("Tag", "Fire｜Ice").unwrap(), "Fire|Ice");
+    assert!(enums.get_value("Tag", "Fire|Earth").is_err());
+    // plain enums are only looked up by their descriptions
+    assert_eq!(enums.get_value("Kind", "远程").unwrap(), "Ranged");
+    assert!(enums.get_value("Kind", "Ranged").is_err());
+    assert!(enums.get_value("Kind", "近战|远程").is_err());
+
+    let mut code = String::new();
+    crate::types::r#enum::Enum {
+        ty: TypeInfo::Enum("Skill".into(), "Tag".into()),
+        ident: "Fire|Ice".into(),
+        is_null: false,
+    }
+    .code_fmt(&mut code)
+    .unwrap();
+    assert_eq!(code, "ESkillTag.Fire | ESkillTag.Ice");
+}
+
+#[test]
+fn test_enum_count() {
+    let sheet = |rows: &[[&'static str; 3]]| {
+        let mut data = Vec::new();
+        for row in rows {
+            let mut cells = vec![""; 3];
+            cells[CFG.col_of_enum_ident] = row[0];
+            cells[CFG.col_of_enum_val] = row[1];
+            cells[CFG.col_of_enum_desc] = row[2];
+            data.push(VectorView(cells.into_boxed_slice()));
+        }
+        Sheet {
+            col: 3,
+            row: data.len(),
+            data: data.into_boxed_slice(),
+        }
+    };
+
+    let ok = sheet(&[["Fire", "0", "火"], ["Ice", "1", "冰"], ["Wind", "5", "风"]]);
+    assert!(Enums::validate(&ok, "Kind", false).is_ok());
+
+    // `Count` would be 2 like `Wind`
+    let collision = sheet(&[["Fire", "0", "火"], ["Wind", "2", "风"], ["Ice", "1", "冰"]]);
+    let e = Enums::validate(&collision, "Kind", false)
+        .unwrap_err()
+        .to_string();
+    assert!(e.contains("`Count` = 2 collides with `Wind` in row 2"));
+    // flags enums have no `Count`
+    assert!(Enums::validate(&collision, "Kind", true).is_ok());
+
+    let reserved = sheet(&[["Fire", "0", "火"], ["Count", "1", "数量"]]);
+    assert!(Enums::validate(&reserved, "Kind", false).is_err());
+}
+
+#[test]
+fn test_lstring_ids() {
+    let mut ids = LStringIds {
+        path: String::new(),
+        ids: HashMap::new(),
+        dirty: false,
+    };
+    assert_eq!(ids.assign(&["a", "", "b", "c"]), vec![0, 1, 2, 3]);
+    assert!(ids.dirty);
+
+    // an edited row only touches its own text, the freed id is reused by the next new text
+    ids.dirty = false;
+    assert_eq!(ids.assign(&["a", "", "B", "c"]), vec![0, 1, 2, 3]);
+    assert_eq!(ids.assign(&["a", "", "c"]), vec![0, 1, 3]);
+    assert_eq!(ids.assign(&["d", "a", "", "c", "e"]), vec![2, 0, 1, 3, 4]);
+    ids.dirty = false;
+    assert_eq!(ids.assign(&["d", "a", "", "c", "e"]), vec![2, 0, 1, 3, 4]);
+    assert!(!ids.dirty);
+}
+
+#[test]
+fn test_visit_nested_lstrings() {
+    let types: &[&'static str] = &[
+        "int",
+        "LString",
+        "List<ValueTuple<LString,int>>",
+        "LString[][]",
+        "Dictionary<int,LString>",
+    ];
+    let mut rows = (0..CFG.row_of_start)
+        .map(|_| VectorView(vec![""; types.len()].into_boxed_slice()))
+        .collect::<Vec<_>>();
+    rows[CFG.row_of_type] = VectorView(types.to_vec().into_boxed_slice());
+    rows.push(VectorView(
+        vec![
+            "1",
+            "\"剑\"",
+            "{{\"a, b\", 1}, {\"\", 2}}",
+            "{{x, y}, {}}",
+            "{{1, \"q\"}}",
+        ]
+        .into_boxed_slice(),
+    ));
+    rows.push(VectorView(vec!["2", "", "", "", ""].into_boxed_slice()));
+    let sheet = Sheet {
+        col: types.len(),
+        row: rows.len(),
+        data: rows.into_boxed_slice(),
+    };
+
+    let mut visited = Vec::new();
+    assert!(visit_lstrings(&sheet, |(c, _), v| {
+        visited.push((c, v.map(|v| v.into_owned())));
+        Ok(())
+    })
+    .unwrap());
+    let some = |v: &str| Some(v.to_string());
+    assert_eq!(
+        visited,
+        vec![
+            (1, some("剑")),
+            (2, some("a, b")),
+            (2, None),
+            (3, some("x")),
+            (3, some("y")),
+            (3, None),
+            (4, some("q")),
+            (1, None),
+        ]
+    );
+}
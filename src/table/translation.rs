@@ -0,0 +1,706 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Write,
+    path::Path,
+};
+
+use xlsx_read::vnxml::{self, XmlElement};
+
+use crate::{
+    config::{self, CFG},
+    error::Error,
+    escape, util,
+};
+
+use super::{language::Languages, markup, template, Sheet, Table, TableEntity};
+
+pub enum TranslationFormat {
+    Xliff,
+    Po,
+    Invalid,
+}
+
+impl From<&str> for TranslationFormat {
+    fn from(value: &str) -> Self {
+        match value {
+            "xliff" | "XLIFF" | "xlf" => Self::Xliff,
+            "po" | "PO" | "gettext" => Self::Po,
+            _ => Self::Invalid,
+        }
+    }
+}
+
+impl TranslationFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Xliff => "xlf",
+            Self::Po => "po",
+            Self::Invalid => "",
+        }
+    }
+}
+
+/// a localizable text, it's identified by (context, source)
+#[derive(Default)]
+pub struct Unit {
+    // `LString.{key}` for the LString sheets, the table name for the texts of a table
+    pub context: String,
+    pub source: String,
+    pub target: String,
+    // `{sheet}!{col}{row}` of the cells holding the text
+    pub locations: Vec<String>,
+}
+
+/// the localizable texts with their translations in one language
+#[derive(Default)]
+pub struct Catalog {
+    pub lang: String,
+    pub units: Vec<Unit>,
+}
+
+impl Catalog {
+    /// texts of the LString sheets and of the LString cells of every table, the translations
+    /// come from the overlay first and then from the LString sheets
+    pub fn collect(entities: &[TableEntity], lang: &str) -> Result<Self, Error> {
+        let overlay = Overlay::load(lang)?;
+        let mut catalog = Catalog {
+            lang: lang.into(),
+            units: Vec::new(),
+        };
+
+        for entity in entities {
+            match entity {
+                TableEntity::Language(sheets) => {
+                    for (name, sheet) in sheets.iter() {
+                        let sheet = Sheet::load(sheet)?;
+                        let column_of = |lang: &str| {
+                            Languages::column_of(&sheet, lang)
+                                .map_err(|e| format!("In the sheet {} of LString: {}", name, e))
+                        };
+                        let source = column_of(CFG.source_language())?;
+                        let target = column_of(lang)?;
+                        for r in 2..sheet.row {
+                            let key = sheet.cell(0, r, true)?;
+                            let text = sheet.cell(source, r, false)?;
+                            if key.is_empty() || text.trim().is_empty() {
+                                continue;
+                            }
+                            let context = format!("LString.{}", key);
+                            let translated = match overlay.get(&context, text) {
+                                Some(v) => v,
+                                None if target != source => sheet.cell(target, r, false)?,
+                                None => "",
+                            };
+                            catalog.units.push(Unit {
+                                target: translated.into(),
+                                source: text.into(),
+                                context,
+                                locations: vec![location(name, source, r)],
+                            });
+                        }
+                    }
+                }
+                TableEntity::Template(name, Some(table), _, _) => {
+                    let mut sheet = Sheet::load(table)?;
+                    sheet.row = Table::get_sheet_height(table, None)?;
+                    // the same text in a table is translated once
+                    let mut index = HashMap::new();
+                    template::visit_lstrings(&sheet, |(c, r), v| {
+                        let Some(v) = v else { return Ok(()) };
+                        let idx = *index.entry(v).or_insert_with_key(|v| {
+                            catalog.units.push(Unit {
+                                context: name.clone(),
+                                source: v.to_string(),
+                                target: overlay.get(name, v).unwrap_or("").into(),
+                                locations: Vec::new(),
+                            });
+                            catalog.units.len() - 1
+                        });
+                        catalog.units[idx].locations.push(location(name, c, r));
+                        Ok(())
+                    })
+                    .map_err(|e| format!("In table {}, collect lstrings failed: {}", name, e))?;
+                }
+                _ => {}
+            }
+        }
+        Ok(catalog)
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let ret = match path.extension().and_then(|v| v.to_str()) {
+            Some("po") => Self::read_po(&std::fs::read_to_string(path)?),
+            Some("xlf" | "xliff") => Self::read_xliff(std::fs::File::open(path)?),
+            _ => Err("expected a `.po`, `.xlf` or `.xliff` file".into()),
+        };
+        ret.map_err(|e| format!("Read {} failed: {}", path.display(), e).into())
+    }
+
+    pub fn write_po<W: Write + ?Sized>(&self, stream: &mut W) -> Result<(), Error> {
+        writeln!(stream, "msgid \"\"")?;
+        writeln!(stream, "msgstr \"\"")?;
+        writeln!(stream, "\"Language: {}\\n\"", self.lang)?;
+        writeln!(
+            stream,
+            "\"X-Source-Language: {}\\n\"",
+            CFG.source_language()
+        )?;
+        writeln!(stream, "\"MIME-Version: 1.0\\n\"")?;
+        writeln!(stream, "\"Content-Type: text/plain; charset=UTF-8\\n\"")?;
+        writeln!(stream, "\"Content-Transfer-Encoding: 8bit\\n\"")?;
+        for unit in self.units.iter() {
+            writeln!(stream)?;
+            if !unit.locations.is_empty() {
+                writeln!(stream, "#: {}", unit.locations.join(" "))?;
+            }
+            writeln!(stream, "msgctxt {}", escape::po(&unit.context))?;
+            writeln!(stream, "msgid {}", escape::po(&unit.source))?;
+            writeln!(stream, "msgstr {}", escape::po(&unit.target))?;
+        }
+        Ok(())
+    }
+
+    pub fn write_xliff<W: Write + ?Sized>(&self, stream: &mut W) -> Result<(), Error> {
+        writeln!(stream, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            stream,
+            r#"<xliff version="1.2" xmlns="urn:oasis:names:tc:xliff:document:1.2">"#
+        )?;
+        writeln!(
+            stream,
+            r#"  <file original="config" datatype="plaintext" source-language="{}" target-language="{}">"#,
+            CFG.source_language(),
+            escape::xml(&self.lang)
+        )?;
+        writeln!(stream, "    <body>")?;
+        for (i, unit) in self.units.iter().enumerate() {
+            writeln!(
+                stream,
+                r#"      <trans-unit id="{}" resname="{}" xml:space="preserve">"#,
+                i + 1,
+                escape::xml(&unit.context)
+            )?;
+            writeln!(
+                stream,
+                "        <source>{}</source>",
+                escape::xml(&unit.source)
+            )?;
+            writeln!(
+                stream,
+                "        <target>{}</target>",
+                escape::xml(&unit.target)
+            )?;
+            if !unit.locations.is_empty() {
+                writeln!(stream, r#"        <context-group purpose="location">"#)?;
+                for v in unit.locations.iter() {
+                    writeln!(
+                        stream,
+                        r#"          <context context-type="x-cell">{}</context>"#,
+                        escape::xml(v)
+                    )?;
+                }
+                writeln!(stream, "        </context-group>")?;
+            }
+            writeln!(stream, "      </trans-unit>")?;
+        }
+        writeln!(stream, "    </body>")?;
+        writeln!(stream, "  </file>")?;
+        writeln!(stream, "</xliff>")?;
+        Ok(())
+    }
+
+    fn read_po(src: &str) -> Result<Self, Error> {
+        #[derive(Clone, Copy)]
+        enum Field {
+            Context,
+            Source,
+            Target,
+        }
+
+        let mut catalog = Catalog::default();
+        let mut unit = Unit::default();
+        // the unit has a msgid and is finished by the next msgctxt, msgid or blank line
+        let mut started = false;
+        let mut field = None;
+        let mut flush = |unit: &mut Unit, started: &mut bool| {
+            let unit = std::mem::take(unit);
+            if !std::mem::take(started) {
+                return;
+            }
+            if unit.context.is_empty() && unit.source.is_empty() {
+                // header entry
+                for v in unit.target.lines() {
+                    if let Some(v) = v.strip_prefix("Language:") {
+                        catalog.lang = v.trim().into();
+                    }
+                }
+            } else {
+                catalog.units.push(unit);
+            }
+        };
+
+        for (i, line) in src.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                flush(&mut unit, &mut started);
+                field = None;
+                continue;
+            }
+            if let Some(v) = line.strip_prefix("#:") {
+                if started {
+                    flush(&mut unit, &mut started);
+                }
+                unit.locations
+                    .extend(v.split_whitespace().map(String::from));
+                continue;
+            }
+            if line.starts_with('#') {
+                continue;
+            }
+
+            let (keyword, raw) = match line.find(char::is_whitespace) {
+                _ if line.starts_with('"') => ("", line),
+                Some(idx) => (&line[..idx], line[idx..].trim()),
+                None => (line, ""),
+            };
+            if raw.len() < 2 || !raw.starts_with('"') || !raw.ends_with('"') {
+                return Err(format!("line {}: expected a quoted string", i + 1).into());
+            }
+            let text = escape::po_unquote(&raw[1..raw.len() - 1]);
+            match keyword {
+                "msgctxt" => {
+                    if started {
+                        flush(&mut unit, &mut started);
+                    }
+                    unit.context = text.into();
+                    field = Some(Field::Context);
+                }
+                "msgid" => {
+                    if started {
+                        flush(&mut unit, &mut started);
+                    }
+                    started = true;
+                    unit.source = text.into();
+                    field = Some(Field::Source);
+                }
+                "msgstr" => {
+                    unit.target = text.into();
+                    field = Some(Field::Target);
+                }
+                "" => match field {
+                    Some(Field::Context) => unit.context.push_str(&text),
+                    Some(Field::Source) => unit.source.push_str(&text),
+                    Some(Field::Target) => unit.target.push_str(&text),
+                    None => return Err(format!("line {}: unexpected string", i + 1).into()),
+                },
+                v => return Err(format!("line {}: unsupported keyword `{}`", i + 1, v).into()),
+            }
+        }
+        flush(&mut unit, &mut started);
+        Ok(catalog)
+    }
+
+    fn read_xliff<R: std::io::Read>(src: R) -> Result<Self, Error> {
+        let root = vnxml::read_from(src)
+            .map_err(|e| format!("invalid xml: {}", e))?
+            .ok_or::<Error>("empty document".into())?;
+        let mut catalog = Catalog::default();
+        for file in elements(&root, "file") {
+            let lang = file
+                .get_attribute(|v| v.local_name == "target-language")
+                .ok_or::<Error>("lack of the `target-language` of <file>".into())?;
+            if !catalog.lang.is_empty() && catalog.lang != *lang {
+                return Err(format!("mixed target languages {} and {}", catalog.lang, lang).into());
+            }
+            catalog.lang = lang.clone();
+            for unit in elements(file, "body").flat_map(|v| elements(v, "trans-unit")) {
+                let id = unit
+                    .get_attribute(|v| v.local_name == "id")
+                    .map_or("", |v| v.as_str());
+                let context = unit
+                    .get_attribute(|v| v.local_name == "resname")
+                    .ok_or::<Error>(
+                        format!("lack of the `resname` of <trans-unit id=\"{}\">", id).into(),
+                    )?;
+                catalog.units.push(Unit {
+                    context: context.clone(),
+                    source: elements(unit, "source")
+                        .next()
+                        .map(text)
+                        .unwrap_or_default(),
+                    target: elements(unit, "target")
+                        .next()
+                        .map(text)
+                        .unwrap_or_default(),
+                    locations: elements(unit, "context-group")
+                        .flat_map(|v| elements(v, "context"))
+                        .map(text)
+                        .collect(),
+                });
+            }
+        }
+        Ok(catalog)
+    }
+}
+
+/// imported translations of a language, keyed by context and then by the source text, so a
+/// translation is dropped once its source text is changed
+#[derive(Default)]
+pub struct Overlay(HashMap<String, HashMap<String, String>>);
+
+impl Overlay {
+    pub fn load(lang: &str) -> Result<Self, Error> {
+        let mut overlay = Self::default();
+        for unit in Self::catalog(lang)?.units {
+            if !unit.target.is_empty() {
+                overlay
+                    .0
+                    .entry(unit.context)
+                    .or_default()
+                    .insert(unit.source, unit.target);
+            }
+        }
+        Ok(overlay)
+    }
+
+    pub fn get(&self, context: &str, source: &str) -> Option<&str> {
+        self.0
+            .get(context)
+            .and_then(|v| v.get(source))
+            .map(|v| v.as_str())
+    }
+
+    /// merge the translated units of `catalog` into the overlay file of its language, returns
+    /// the number of the merged units
+    pub fn import(catalog: Catalog) -> Result<usize, Error> {
+        let lang = CFG.language(&catalog.lang).ok_or::<Error>(
+            format!(
+                "Invalid language `{}`, expected one of {:?}",
+                catalog.lang, CFG.languages
+            )
+            .into(),
+        )?;
+        let broken = catalog
+            .units
+            .iter()
+            .filter(|v| !v.target.is_empty())
+            .filter_map(|v| {
+                markup::check(&v.source, &v.target)
+                    .err()
+                    .map(|e| format!("{} `{}`: {}", v.context, v.source, e))
+            })
+            .collect::<Vec<_>>();
+        if !broken.is_empty() {
+            return Err(broken.join("\n").into());
+        }
+
+        let mut current = Self::catalog(lang)?;
+        current.lang = lang.into();
+        let mut index = current
+            .units
+            .iter()
+            .enumerate()
+            .map(|(i, v)| ((v.context.clone(), v.source.clone()), i))
+            .collect::<HashMap<_, _>>();
+
+        let mut count = 0;
+        for unit in catalog.units.into_iter().filter(|v| !v.target.is_empty()) {
+            count += 1;
+            match index.get(&(unit.context.clone(), unit.source.clone())) {
+                Some(i) => current.units[*i] = unit,
+                None => {
+                    index.insert(
+                        (unit.context.clone(), unit.source.clone()),
+                        current.units.len(),
+                    );
+                    current.units.push(unit);
+                }
+            }
+        }
+
+        let path = config::translation_path(lang);
+        if let Some(dir) = Path::new(&path).parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = std::fs::File::create(&path)?;
+        current.write_po(&mut file)?;
+        file.flush()?;
+        Ok(count)
+    }
+
+    fn catalog(lang: &str) -> Result<Catalog, Error> {
+        let path = config::translation_path(lang);
+        match std::fs::read_to_string(&path) {
+            Ok(v) => Catalog::read_po(&v)
+                .map_err(|e| format!("Read the translations {} failed: {}", path, e).into()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Catalog::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// hashes of the source text and the translation of every key, recorded when the translation
+/// changes, so a translation whose source text changed alone afterwards is stale
+pub struct SourceHashes {
+    path: String,
+    hashes: BTreeMap<String, (u64, u64)>,
+    dirty: bool,
+}
+
+impl SourceHashes {
+    pub fn load(lang: &str) -> Result<Self, Error> {
+        let path = config::translation_hash_path(lang);
+        let mut hashes = BTreeMap::new();
+        match std::fs::read_to_string(&path) {
+            Ok(v) => {
+                let hash = |v: &str| u64::from_str_radix(v, 16).ok();
+                for (i, line) in v.lines().enumerate().filter(|v| !v.1.trim().is_empty()) {
+                    match line.split('\t').collect::<Vec<_>>().as_slice() {
+                        [key, source, target] => match (hash(source), hash(target)) {
+                            (Some(source), Some(target)) => {
+                                hashes.insert(key.to_string(), (source, target));
+                            }
+                            _ => {
+                                return Err(format!("{}: line {}, invalid hash", path, i + 1).into())
+                            }
+                        },
+                        _ => return Err(format!("{}: line {}, invalid format", path, i + 1).into()),
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        Ok(Self {
+            path,
+            hashes,
+            dirty: false,
+        })
+    }
+
+    /// record the texts of `key`, returns true if the translation is stale
+    pub fn check(&mut self, key: &str, source: &str, target: &str) -> bool {
+        let current = (fnv1a(source), fnv1a(target));
+        match self.hashes.get(key) {
+            Some(v) if v.1 == current.1 => v.0 != current.0,
+            _ => {
+                self.hashes.insert(key.into(), current);
+                self.dirty = true;
+                false
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(dir) = Path::new(&self.path).parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = std::io::BufWriter::new(std::fs::File::create(&self.path)?);
+        for (key, (source, target)) in self.hashes.iter() {
+            writeln!(file, "{}\t{:016x}\t{:016x}", key, source, target)?;
+        }
+        file.flush()?;
+        Ok(())
+    }
+}
+
+pub enum Issue {
+    // the key has no text in the language
+    Missing(String, String),
+    // the source text changed after the translation was made
+    Stale(String, String),
+    // the sheet has no column of the language, none of its keys is translated
+    NoColumn(String, usize),
+    // a text without key is dropped from the language files
+    NoKey(String),
+    // the placeholders or the tags don't match the source text
+    Format(String, String),
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Issue::Missing(key, location) => write!(f, "missing  {} ({})", key, location),
+            Issue::Stale(key, location) => write!(f, "stale    {} ({})", key, location),
+            Issue::NoColumn(sheet, count) => {
+                write!(f, "no column in the sheet {}, {} keys", sheet, count)
+            }
+            Issue::NoKey(location) => write!(f, "no key   {}", location),
+            Issue::Format(location, e) => write!(f, "format   {}: {}", location, e),
+        }
+    }
+}
+
+/// missing and stale translations of the LString sheets
+pub struct Report {
+    pub issues: Vec<(&'static str, Vec<Issue>)>,
+}
+
+impl Report {
+    pub fn collect(entities: &[TableEntity], langs: &[&'static str]) -> Result<Self, Error> {
+        let mut issues = Vec::new();
+        for lang in langs.iter().filter(|v| **v != CFG.source_language()) {
+            let overlay = Overlay::load(lang)?;
+            let mut hashes = SourceHashes::load(lang)?;
+            let mut found = Vec::new();
+            for sheets in entities.iter().filter_map(|v| match v {
+                TableEntity::Language(sheets) => Some(sheets),
+                _ => None,
+            }) {
+                for (name, sheet) in sheets.iter() {
+                    let sheet = Sheet::load(sheet)?;
+                    let source = Languages::column_of(&sheet, CFG.source_language())
+                        .map_err(|e| format!("In the sheet {} of LString: {}", name, e))?;
+                    let Ok(target) = Languages::column_of(&sheet, lang) else {
+                        let keys = (2..sheet.row)
+                            .filter(|r| sheet.cell(0, *r, true).is_ok_and(|v| !v.is_empty()))
+                            .count();
+                        found.push(Issue::NoColumn(name.clone(), keys));
+                        continue;
+                    };
+                    for r in 2..sheet.row {
+                        let key = sheet.cell(0, r, true)?;
+                        let text = sheet.cell(source, r, false)?;
+                        if key.is_empty() {
+                            if !text.trim().is_empty() {
+                                found.push(Issue::NoKey(location(name, 0, r)));
+                            }
+                            continue;
+                        }
+                        let context = format!("LString.{}", key);
+                        if let Some(translated) = overlay.get(&context, text) {
+                            if let Err(e) = markup::check(text, translated) {
+                                found.push(Issue::Format(
+                                    format!("{} ({})", key, config::translation_path(lang)),
+                                    e,
+                                ));
+                            }
+                            continue;
+                        }
+                        let translated = sheet.cell(target, r, false)?;
+                        if translated.trim().is_empty() {
+                            if !text.trim().is_empty() {
+                                found.push(Issue::Missing(key.into(), location(name, target, r)));
+                            }
+                        } else if let Err(e) = markup::check(text, translated) {
+                            found.push(Issue::Format(location(name, target, r), e));
+                        } else if hashes.check(key, text, translated) {
+                            found.push(Issue::Stale(key.into(), location(name, target, r)));
+                        }
+                    }
+                }
+            }
+            // only the build records the hashes, a report leaves them as they were
+            issues.push((*lang, found));
+        }
+        Ok(Self { issues })
+    }
+
+    pub fn write<W: Write + ?Sized>(&self, stream: &mut W) -> Result<(), Error> {
+        for (lang, issues) in self.issues.iter() {
+            writeln!(stream, "[{}] {} issues", lang, issues.len())?;
+            for v in issues.iter() {
+                writeln!(stream, "    {}", v)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 64-bit FNV-1a, stable between runs and platforms
+fn fnv1a(val: &str) -> u64 {
+    val.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn location(sheet: &str, col: usize, row: usize) -> String {
+    format!("{}!{}{}", sheet, util::conv_col_idx(col + 1), row + 1)
+}
+
+fn elements<'x>(parent: &'x XmlElement, name: &'x str) -> impl Iterator<Item = &'x XmlElement> {
+    parent
+        .children
+        .iter()
+        .filter_map(|v| v.downcast_ref().to_element())
+        .filter(move |v| v.name.local_name == name)
+}
+
+fn text(element: &XmlElement) -> String {
+    element
+        .children
+        .iter()
+        .filter_map(|v| v.downcast_ref().to_text())
+        .map(|v| v.content.as_str())
+        .collect()
+}
+
+#[test]
+fn test_translation_formats() {
+    let catalog = Catalog {
+        lang: "EN".into(),
+        units: vec![
+            Unit {
+                context: "LString.Hello".into(),
+                source: "你好, \"{0}\"\n<b>欢迎</b> & 再见".into(),
+                target: "Hello, \"{0}\"\n<b>Welcome</b> & bye".into(),
+                locations: vec!["UI!B3".into()],
+            },
+            Unit {
+                context: "Item".into(),
+                source: "长剑".into(),
+                target: String::new(),
+                locations: vec!["Item!E9".into(), "Item!E12".into()],
+            },
+        ],
+    };
+
+    let mut po = Vec::new();
+    catalog.write_po(&mut po).unwrap();
+    let mut xliff = Vec::new();
+    catalog.write_xliff(&mut xliff).unwrap();
+    for read in [
+        Catalog::read_po(std::str::from_utf8(&po).unwrap()).unwrap(),
+        Catalog::read_xliff(xliff.as_slice()).unwrap(),
+    ] {
+        assert_eq!(read.lang, "EN");
+        assert_eq!(read.units.len(), 2);
+        for (a, b) in read.units.iter().zip(catalog.units.iter()) {
+            assert_eq!(a.context, b.context);
+            assert_eq!(a.source, b.source);
+            assert_eq!(a.target, b.target);
+            assert_eq!(a.locations, b.locations);
+        }
+    }
+
+    // multi-line strings of the other tools
+    let read = Catalog::read_po(
+        "msgid \"\"\nmsgstr \"\"\n\"Language: JP\\n\"\n\nmsgctxt \"Item\"\nmsgid \"\"\n\"a\"\n\"b\"\nmsgstr \"x\"\n",
+    )
+    .unwrap();
+    assert_eq!(read.lang, "JP");
+    assert_eq!(read.units[0].source, "ab");
+    assert!(Catalog::read_po("msgid_plural \"a\"").is_err());
+}
+
+#[test]
+fn test_source_hashes() {
+    let mut hashes = SourceHashes {
+        path: String::new(),
+        hashes: BTreeMap::new(),
+        dirty: false,
+    };
+    assert!(!hashes.check("Hello", "你好", "Hello"));
+    assert!(hashes.dirty);
+    assert!(!hashes.check("Hello", "你好", "Hello"));
+    // the source changed but the translation didn't
+    assert!(hashes.check("Hello", "你好呀", "Hello"));
+    assert!(hashes.check("Hello", "你好呀", "Hello"));
+    // the translator caught up
+    assert!(!hashes.check("Hello", "你好呀", "Hi there"));
+    assert!(!hashes.check("Hello", "你好呀", "Hi there"));
+}
@@ -1,11 +1,44 @@
 use super::{TypeInfo, Value};
+use crate::escape;
 
 pub struct Custom {
     pub ty: TypeInfo,
     pub args: Vec<String>,
+    // typed args of a type declared in the schema, `None` for `{}`
+    pub fields: Vec<Option<Box<dyn Value>>>,
     pub is_null: bool,
 }
 
+impl Custom {
+    fn fields_code_fmt(&self, stream: &mut dyn std::fmt::Write) -> Result<(), crate::error::Error> {
+        for (i, v) in self.fields.iter().enumerate() {
+            if i > 0 {
+                stream.write_char(',')?;
+            }
+            match v {
+                Some(v) => v.code_fmt(stream)?,
+                None => stream.write_str("default")?,
+            }
+        }
+        Ok(())
+    }
+
+    fn fields_code(&self, stream: &mut dyn std::io::Write) -> Result<(), crate::error::Error> {
+        for (i, v) in self.fields.iter().enumerate() {
+            if i > 0 {
+                stream.write(",".as_bytes())?;
+            }
+            match v {
+                Some(v) => v.code(stream)?,
+                None => {
+                    stream.write("default".as_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Value for Custom {
     fn ty_fmt(&self, stream: &mut dyn std::fmt::Write) -> Result<(), crate::error::Error> {
         stream.write_fmt(format_args!("{}", self.ty))?;
@@ -18,7 +51,9 @@ impl Value for Custom {
         } else {
             stream.write_fmt(format_args!("new {}(", self.ty))?;
 
-            if !self.args.is_empty() {
+            if !self.fields.is_empty() {
+                self.fields_code_fmt(stream)?;
+            } else if !self.args.is_empty() {
                 for v in (&self.args[0..self.args.len() - 1])
                     .iter()
                     .map(|v| v.as_str().trim())
@@ -26,9 +61,9 @@ impl Value for Custom {
                     if v == "{}" {
                         stream.write_str("default,")?;
                     } else if v.starts_with("{") {
-                        stream.write_fmt(format_args!("new []{},", v.replace(" ", "")))?;
+                        stream.write_fmt(format_args!("new []{},", escape::strip_spaces(v)))?;
                     } else {
-                        stream.write_fmt(format_args!("{},", v.replace(" ", "")))?;
+                        stream.write_fmt(format_args!("{},", escape::strip_spaces(v)))?;
                     }
                 }
 
@@ -36,9 +71,9 @@ impl Value for Custom {
                 if v == "{}" {
                     stream.write_str("default")?;
                 } else if v.starts_with("{") {
-                    stream.write_fmt(format_args!("new []{}", v.replace(" ", "")))?;
+                    stream.write_fmt(format_args!("new []{}", escape::strip_spaces(v)))?;
                 } else {
-                    stream.write_fmt(format_args!("{}", v.replace(" ", "")))?;
+                    stream.write_fmt(format_args!("{}", escape::strip_spaces(v)))?;
                 }
             }
             stream.write_char(')')?;
@@ -50,6 +85,9 @@ impl Value for Custom {
         let TypeInfo::Custom(_) = self.ty else {
             return Err("Check failed, expected custom type".into());
         };
+        for v in self.fields.iter().flatten() {
+            v.check()?;
+        }
         Ok(())
     }
 
@@ -68,7 +106,9 @@ impl Value for Custom {
         } else {
             stream.write_fmt(format_args!("new {}(", self.ty))?;
 
-            if !self.args.is_empty() {
+            if !self.fields.is_empty() {
+                self.fields_code(stream)?;
+            } else if !self.args.is_empty() {
                 for v in (&self.args[0..self.args.len() - 1])
                     .iter()
                     .map(|v| v.as_str().trim())
@@ -76,9 +116,9 @@ impl Value for Custom {
                     if v == "{}" {
                         stream.write("default,".as_bytes())?;
                     } else if v.starts_with("{") {
-                        stream.write_fmt(format_args!("new []{},", v.replace(" ", "")))?;
+                        stream.write_fmt(format_args!("new []{},", escape::strip_spaces(v)))?;
                     } else {
-                        stream.write_fmt(format_args!("{},", v.replace(" ", "")))?;
+                        stream.write_fmt(format_args!("{},", escape::strip_spaces(v)))?;
                     }
                 }
 
@@ -86,9 +126,9 @@ impl Value for Custom {
                 if v == "{}" {
                     stream.write("default".as_bytes())?;
                 } else if v.starts_with("{") {
-                    stream.write_fmt(format_args!("new []{}", v.replace(" ", "")))?;
+                    stream.write_fmt(format_args!("new []{}", escape::strip_spaces(v)))?;
                 } else {
-                    stream.write_fmt(format_args!("{}", v.replace(" ", "")))?;
+                    stream.write_fmt(format_args!("{}", escape::strip_spaces(v)))?;
                 }
             }
             stream.write(")".as_bytes())?;
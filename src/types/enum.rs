@@ -16,8 +16,14 @@ impl Value for Enum {
         if self.is_null {
             stream.write_str("null")?;
         } else {
-            self.ty_fmt(stream)?;
-            stream.write_fmt(format_args!(".{}", self.ident))?;
+            // members of a flags enum are joined by `|`
+            for (i, ident) in self.ident.split('|').enumerate() {
+                if i > 0 {
+                    stream.write_str(" | ")?;
+                }
+                self.ty_fmt(stream)?;
+                stream.write_fmt(format_args!(".{}", ident))?;
+            }
         }
         Ok(())
     }
@@ -42,8 +48,13 @@ impl Value for Enum {
         if self.is_null {
             stream.write("null".as_bytes())?;
         } else {
-            self.ty(stream)?;
-            stream.write_fmt(format_args!(".{}", self.ident))?;
+            for (i, ident) in self.ident.split('|').enumerate() {
+                if i > 0 {
+                    stream.write(" | ".as_bytes())?;
+                }
+                self.ty(stream)?;
+                stream.write_fmt(format_args!(".{}", ident))?;
+            }
         }
         Ok(())
     }
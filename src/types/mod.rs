@@ -4,6 +4,7 @@ use crate::error::Error;
 
 pub(crate) mod custom;
 pub(crate) mod r#enum;
+pub(crate) mod nullable;
 pub(crate) mod numbers;
 pub(crate) mod sequence;
 pub(crate) mod string;
@@ -41,48 +42,101 @@ pub enum TypeInfo {
     ValueTuple(Vec<Box<TypeInfo>>),
     ShortList,
     Custom(String),
+    Dictionary(Box<TypeInfo>, Box<TypeInfo>),
+    Long,
+    ULong,
+    Nullable(Box<TypeInfo>),
+    Duration,
+    DateTime,
 }
 
 impl TypeInfo {
     #[inline]
     pub fn is_string(&self) -> bool {
-        match self {
-            TypeInfo::String => true,
-            _ => false,
-        }
+        matches!(self, TypeInfo::String)
     }
 
     #[inline]
     pub fn is_enum(&self) -> bool {
+        matches!(self, TypeInfo::Enum(_, _))
+    }
+
+    #[inline]
+    pub fn is_array_or_list(&self) -> bool {
+        matches!(
+            self,
+            Self::Array(_) | Self::FixedArray(_, _) | Self::List(_)
+        )
+    }
+
+    #[inline]
+    pub fn is_lstring(&self) -> bool {
+        self == &TypeInfo::LString
+    }
+
+    #[inline]
+    pub fn is_lstring_arr(&self) -> bool {
         match self {
-            TypeInfo::Enum(_, _) => true,
+            TypeInfo::Array(e) if e.as_ref() == &TypeInfo::LString => true,
+            TypeInfo::FixedArray(e, _) if e.as_ref() == &TypeInfo::LString => true,
             _ => false,
         }
     }
 
     #[inline]
-    pub fn is_array_or_list(&self) -> bool {
+    pub fn is_lstring_dict(&self) -> bool {
         match self {
-            Self::Array(_) | Self::FixedArray(_, _) | Self::List(_) => true,
+            TypeInfo::Dictionary(_, v) => v.is_lstring(),
             _ => false,
         }
     }
 
     #[inline]
-    pub fn is_lstring_or_lstringarr(&self) -> bool {
-        self.is_lstring() || self.is_lstring_arr()
+    pub fn is_number(&self) -> bool {
+        matches!(
+            self,
+            TypeInfo::Int
+                | TypeInfo::Uint
+                | TypeInfo::Short
+                | TypeInfo::UShort
+                | TypeInfo::Float
+                | TypeInfo::Double
+                | TypeInfo::Decimal
+                | TypeInfo::Byte
+                | TypeInfo::SByte
+                | TypeInfo::Long
+                | TypeInfo::ULong
+        )
     }
 
     #[inline]
-    pub fn is_lstring(&self) -> bool {
-        self == &TypeInfo::LString
+    pub fn contains_lstring(&self) -> bool {
+        match self {
+            TypeInfo::LString => true,
+            TypeInfo::List(v) | TypeInfo::Array(v) | TypeInfo::FixedArray(v, _) => {
+                v.contains_lstring()
+            }
+            TypeInfo::Tuple(v) | TypeInfo::ValueTuple(v) => v.iter().any(|v| v.contains_lstring()),
+            TypeInfo::Dictionary(k, v) => k.contains_lstring() || v.contains_lstring(),
+            TypeInfo::Nullable(v) => v.contains_lstring(),
+            TypeInfo::Custom(_) => crate::schema::fields_of(self)
+                .is_some_and(|v| v.iter().any(|v| v.info.contains_lstring())),
+            _ => false,
+        }
     }
 
     #[inline]
-    pub fn is_lstring_arr(&self) -> bool {
+    pub fn contains_time(&self) -> bool {
         match self {
-            TypeInfo::Array(e) if e.as_ref() == &TypeInfo::LString => true,
-            TypeInfo::FixedArray(e, _) if e.as_ref() == &TypeInfo::LString => true,
+            TypeInfo::Duration | TypeInfo::DateTime => true,
+            TypeInfo::List(v) | TypeInfo::Array(v) | TypeInfo::FixedArray(v, _) => {
+                v.contains_time()
+            }
+            TypeInfo::Tuple(v) | TypeInfo::ValueTuple(v) => v.iter().any(|v| v.contains_time()),
+            TypeInfo::Dictionary(k, v) => k.contains_time() || v.contains_time(),
+            TypeInfo::Nullable(v) => v.contains_time(),
+            TypeInfo::Custom(_) => crate::schema::fields_of(self)
+                .is_some_and(|v| v.iter().any(|v| v.info.contains_time())),
             _ => false,
         }
     }
@@ -97,6 +151,14 @@ impl TypeInfo {
             TypeInfo::Tuple(v) | TypeInfo::ValueTuple(v) => {
                 v.iter().any(|v| v.contains_string_or_lstring_or_enum())
             }
+            TypeInfo::Dictionary(k, v) => {
+                k.contains_string_or_lstring_or_enum() || v.contains_string_or_lstring_or_enum()
+            }
+            TypeInfo::Nullable(v) => v.contains_string_or_lstring_or_enum(),
+            TypeInfo::Custom(_) => crate::schema::fields_of(self).is_some_and(|v| {
+                v.iter()
+                    .any(|v| v.info.contains_string_or_lstring_or_enum())
+            }),
             _ => false,
         }
     }
@@ -140,6 +202,12 @@ impl Display for TypeInfo {
             }
             TypeInfo::ShortList => f.write_str("ShortList"),
             TypeInfo::Custom(ident) => f.write_str(&ident),
+            TypeInfo::Dictionary(k, v) => f.write_fmt(format_args!("Dictionary<{},{}>", k, v)),
+            TypeInfo::Long => f.write_str("long"),
+            TypeInfo::ULong => f.write_str("ulong"),
+            TypeInfo::Nullable(val) => f.write_fmt(format_args!("{}?", val)),
+            TypeInfo::Duration => f.write_str("System.TimeSpan"),
+            TypeInfo::DateTime => f.write_str("long"),
         }
     }
 }
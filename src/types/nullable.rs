@@ -0,0 +1,55 @@
+use super::{TypeInfo, Value};
+
+pub struct Nullable {
+    pub ty: TypeInfo,
+    pub val: Option<Box<dyn Value>>,
+}
+
+impl Value for Nullable {
+    fn ty_fmt(&self, stream: &mut dyn std::fmt::Write) -> Result<(), crate::error::Error> {
+        stream.write_fmt(format_args!("{}", self.ty))?;
+        Ok(())
+    }
+
+    fn code_fmt(&self, stream: &mut dyn std::fmt::Write) -> Result<(), crate::error::Error> {
+        match self.val {
+            Some(ref v) => v.code_fmt(stream),
+            None => {
+                stream.write_str("null")?;
+                Ok(())
+            }
+        }
+    }
+
+    fn check(&self) -> Result<(), crate::error::Error> {
+        let TypeInfo::Nullable(ref inner) = self.ty else {
+            return Err("Check failed, expected nullable type".into());
+        };
+        match self.val {
+            Some(ref v) if v.ty_info() != inner.as_ref() => {
+                Err(format!("Check failed, expected {} value for {}", inner, self.ty).into())
+            }
+            Some(ref v) => v.check(),
+            None => Ok(()),
+        }
+    }
+
+    fn ty_info(&self) -> &TypeInfo {
+        &self.ty
+    }
+
+    fn ty(&self, stream: &mut dyn std::io::Write) -> Result<(), crate::error::Error> {
+        stream.write_fmt(format_args!("{}", self.ty))?;
+        Ok(())
+    }
+
+    fn code(&self, stream: &mut dyn std::io::Write) -> Result<(), crate::error::Error> {
+        match self.val {
+            Some(ref v) => v.code(stream),
+            None => {
+                stream.write("null".as_bytes())?;
+                Ok(())
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use super::{TypeInfo, Value};
+use crate::config::CFG;
 
 pub struct Bool {
     pub ty: TypeInfo,
@@ -416,3 +417,179 @@ impl Value for Double {
             .map_err(|e| e.into())
     }
 }
+
+pub struct Long {
+    pub ty: TypeInfo,
+    pub val: i64,
+}
+
+impl Value for Long {
+    fn ty_fmt(&self, stream: &mut dyn std::fmt::Write) -> Result<(), crate::error::Error> {
+        stream.write_fmt(format_args!("{}", self.ty))?;
+        Ok(())
+    }
+
+    fn code_fmt(&self, stream: &mut dyn std::fmt::Write) -> Result<(), crate::error::Error> {
+        stream
+            .write_fmt(format_args!("{}", self.val))
+            .map_err(|e| e.into())
+    }
+
+    fn check(&self) -> Result<(), crate::error::Error> {
+        if self.ty == TypeInfo::Long {
+            Ok(())
+        } else {
+            Err("Check failed, expected long type".into())
+        }
+    }
+
+    fn ty_info(&self) -> &TypeInfo {
+        &self.ty
+    }
+
+    fn ty(&self, stream: &mut dyn std::io::Write) -> Result<(), crate::error::Error> {
+        stream.write_fmt(format_args!("{}", self.ty))?;
+        Ok(())
+    }
+
+    fn code(&self, stream: &mut dyn std::io::Write) -> Result<(), crate::error::Error> {
+        stream
+            .write_fmt(format_args!("{}", self.val))
+            .map_err(|e| e.into())
+    }
+}
+
+pub struct ULong {
+    pub ty: TypeInfo,
+    pub val: u64,
+}
+
+impl Value for ULong {
+    fn ty_fmt(&self, stream: &mut dyn std::fmt::Write) -> Result<(), crate::error::Error> {
+        stream.write_fmt(format_args!("{}", self.ty))?;
+        Ok(())
+    }
+
+    fn code_fmt(&self, stream: &mut dyn std::fmt::Write) -> Result<(), crate::error::Error> {
+        stream
+            .write_fmt(format_args!("{}", self.val))
+            .map_err(|e| e.into())
+    }
+
+    fn check(&self) -> Result<(), crate::error::Error> {
+        if self.ty == TypeInfo::ULong {
+            Ok(())
+        } else {
+            Err("Check failed, expected ulong type".into())
+        }
+    }
+
+    fn ty_info(&self) -> &TypeInfo {
+        &self.ty
+    }
+
+    fn ty(&self, stream: &mut dyn std::io::Write) -> Result<(), crate::error::Error> {
+        stream.write_fmt(format_args!("{}", self.ty))?;
+        Ok(())
+    }
+
+    fn code(&self, stream: &mut dyn std::io::Write) -> Result<(), crate::error::Error> {
+        stream
+            .write_fmt(format_args!("{}", self.val))
+            .map_err(|e| e.into())
+    }
+}
+
+/// a time span counted in `CFG.time_unit`
+pub struct Duration {
+    pub ty: TypeInfo,
+    pub val: i64,
+}
+
+impl Duration {
+    fn ctor(&self) -> &'static str {
+        if CFG.time_unit == "s" {
+            "FromSeconds"
+        } else {
+            "FromMilliseconds"
+        }
+    }
+}
+
+impl Value for Duration {
+    fn ty_fmt(&self, stream: &mut dyn std::fmt::Write) -> Result<(), crate::error::Error> {
+        stream.write_fmt(format_args!("{}", self.ty))?;
+        Ok(())
+    }
+
+    fn code_fmt(&self, stream: &mut dyn std::fmt::Write) -> Result<(), crate::error::Error> {
+        stream
+            .write_fmt(format_args!("{}.{}({})", self.ty, self.ctor(), self.val))
+            .map_err(|e| e.into())
+    }
+
+    fn check(&self) -> Result<(), crate::error::Error> {
+        if self.ty == TypeInfo::Duration {
+            Ok(())
+        } else {
+            Err("Check failed, expected duration type".into())
+        }
+    }
+
+    fn ty_info(&self) -> &TypeInfo {
+        &self.ty
+    }
+
+    fn ty(&self, stream: &mut dyn std::io::Write) -> Result<(), crate::error::Error> {
+        stream.write_fmt(format_args!("{}", self.ty))?;
+        Ok(())
+    }
+
+    fn code(&self, stream: &mut dyn std::io::Write) -> Result<(), crate::error::Error> {
+        stream
+            .write_fmt(format_args!("{}.{}({})", self.ty, self.ctor(), self.val))
+            .map_err(|e| e.into())
+    }
+}
+
+/// an unix timestamp (UTC) counted in `CFG.time_unit`
+pub struct DateTime {
+    pub ty: TypeInfo,
+    pub val: i64,
+}
+
+impl Value for DateTime {
+    fn ty_fmt(&self, stream: &mut dyn std::fmt::Write) -> Result<(), crate::error::Error> {
+        stream.write_fmt(format_args!("{}", self.ty))?;
+        Ok(())
+    }
+
+    fn code_fmt(&self, stream: &mut dyn std::fmt::Write) -> Result<(), crate::error::Error> {
+        stream
+            .write_fmt(format_args!("{}", self.val))
+            .map_err(|e| e.into())
+    }
+
+    fn check(&self) -> Result<(), crate::error::Error> {
+        if self.ty == TypeInfo::DateTime {
+            Ok(())
+        } else {
+            Err("Check failed, expected datetime type".into())
+        }
+    }
+
+    fn ty_info(&self) -> &TypeInfo {
+        &self.ty
+    }
+
+    fn ty(&self, stream: &mut dyn std::io::Write) -> Result<(), crate::error::Error> {
+        stream.write_fmt(format_args!("{}", self.ty))?;
+        Ok(())
+    }
+
+    fn code(&self, stream: &mut dyn std::io::Write) -> Result<(), crate::error::Error> {
+        stream
+            .write_fmt(format_args!("{}", self.val))
+            .map_err(|e| e.into())
+    }
+}
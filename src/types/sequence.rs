@@ -411,3 +411,84 @@ impl Value for ValueTuple {
         Ok(())
     }
 }
+
+pub struct Dictionary {
+    pub ty: TypeInfo,
+    pub vals: Vec<(Box<dyn Value>, Box<dyn Value>)>,
+    pub is_null: bool,
+}
+
+impl Value for Dictionary {
+    fn ty_fmt(&self, stream: &mut dyn std::fmt::Write) -> Result<(), crate::error::Error> {
+        stream.write_fmt(format_args!("{}", self.ty))?;
+        Ok(())
+    }
+
+    fn code_fmt(&self, stream: &mut dyn std::fmt::Write) -> Result<(), crate::error::Error> {
+        if self.is_null {
+            stream.write_str("null")?;
+        } else {
+            stream.write_fmt(format_args!("new {}{{", self.ty))?;
+            for (i, (k, v)) in self.vals.iter().enumerate() {
+                if i > 0 {
+                    stream.write_char(',')?;
+                }
+                stream.write_char('{')?;
+                k.code_fmt(stream)?;
+                stream.write_char(',')?;
+                v.code_fmt(stream)?;
+                stream.write_char('}')?;
+            }
+            stream.write_char('}')?;
+        }
+        Ok(())
+    }
+
+    fn check(&self) -> Result<(), crate::error::Error> {
+        let TypeInfo::Dictionary(_, _) = self.ty else {
+            return Err("Check failed, expected dictionary type".into());
+        };
+
+        let mut keys = std::collections::HashSet::with_capacity(self.vals.len());
+        for (k, v) in self.vals.iter() {
+            k.check()?;
+            v.check()?;
+            let mut key = String::new();
+            k.code_fmt(&mut key)?;
+            if keys.contains(&key) {
+                return Err(format!("Duplicate key `{}` in {}", key, self.ty).into());
+            }
+            keys.insert(key);
+        }
+        Ok(())
+    }
+
+    fn ty_info(&self) -> &TypeInfo {
+        &self.ty
+    }
+
+    fn ty(&self, stream: &mut dyn std::io::Write) -> Result<(), crate::error::Error> {
+        stream.write_fmt(format_args!("{}", self.ty))?;
+        Ok(())
+    }
+
+    fn code(&self, stream: &mut dyn std::io::Write) -> Result<(), crate::error::Error> {
+        if self.is_null {
+            stream.write("null".as_bytes())?;
+        } else {
+            stream.write_fmt(format_args!("new {}{{", self.ty))?;
+            for (i, (k, v)) in self.vals.iter().enumerate() {
+                if i > 0 {
+                    stream.write(",".as_bytes())?;
+                }
+                stream.write("{".as_bytes())?;
+                k.code(stream)?;
+                stream.write(",".as_bytes())?;
+                v.code(stream)?;
+                stream.write("}".as_bytes())?;
+            }
+            stream.write("}".as_bytes())?;
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use super::{TypeInfo, Value};
+use crate::escape;
 
 pub struct SString {
     pub ty: TypeInfo,
@@ -17,11 +18,8 @@ impl Value for SString {
             stream.write_str("null")?;
             Ok(())
         } else {
-            if self.val.contains("\\") {
-                stream.write_char('@')?;
-            }
             stream
-                .write_fmt(format_args!("\"{}\"", self.val))
+                .write_str(&escape::csharp(&self.val))
                 .map_err(|e| e.into())
         }
     }
@@ -48,11 +46,9 @@ impl Value for SString {
             stream.write("null".as_bytes())?;
             Ok(())
         } else {
-            if self.val.contains("\\") {
-                stream.write("@".as_bytes())?;
-            }
             stream
-                .write_fmt(format_args!("\"{}\"", self.val))
+                .write(escape::csharp(&self.val).as_bytes())
+                .map(|_| ())
                 .map_err(|e| e.into())
         }
     }
@@ -2,13 +2,18 @@ use std::{
     collections::HashMap,
     hint::unreachable_unchecked,
     path::Path,
+    rc::Rc,
     sync::atomic::{AtomicPtr, Ordering},
 };
 
-use xlsx_read::excel_file::ExcelFile;
+use ansi_term::Colour::Yellow;
+use xlsx_read::{
+    excel_file::ExcelFile,
+    excel_table::{CellValue, ExcelTable},
+};
 
 use crate::{
-    config::CFG,
+    config::{self, CFG},
     error::Error,
     preconfig::PRECONFIG,
     table::{ExcelTableWrapper, TableEntity},
@@ -88,8 +93,10 @@ pub fn load_execl_table<P: AsRef<Path>>(path: P, name: &str) -> Result<TableEnti
     let sheets = excel.parse_workbook()?;
     let mut entity = TableEntity::Invalid;
 
-    for (flag, id) in sheets.into_iter() {
-        let sheet = excel.parse_sheet(id)?;
+    for (flag, part) in sheets.into_iter() {
+        let mut sheet = excel.parse_sheet(&part)?;
+        report_error_values(name, &flag, &sheet);
+        convert_dates(&flag, &mut sheet);
         match flag.as_str() {
             "Template" => {
                 entity = TableEntity::new_template(name);
@@ -108,11 +115,15 @@ pub fn load_execl_table<P: AsRef<Path>>(path: P, name: &str) -> Result<TableEnti
             "FK" => {
                 entity = TableEntity::new_fk(name, ExcelTableWrapper(sheet));
             }
-            v if v.starts_with("t_") => {
+            v if v.starts_with("t_") || v.starts_with("f_") => {
                 let TableEntity::Template(_, _, ref mut enums, _) = entity else {
                     return Err(format!("The {}.xlsx is missing a `Template` sheet", name).into());
                 };
-                enums.push(((&v[2..]).into(), ExcelTableWrapper(sheet)));
+                enums.push((
+                    (&v[2..]).into(),
+                    ExcelTableWrapper(sheet),
+                    v.starts_with("f_"),
+                ));
             }
             v if name == "LString" => match entity {
                 TableEntity::Language(ref mut langs) => {
@@ -140,6 +151,83 @@ pub fn load_execl_table<P: AsRef<Path>>(path: P, name: &str) -> Result<TableEnti
     Ok(entity)
 }
 
+/// date formatted cells keep their serial numbers unless they're in a `datetime` column, the
+/// ones there are read as the `"2024-05-01 10:00:00"` of DateTime
+fn convert_dates(flag: &str, sheet: &mut ExcelTable) {
+    let is_datetime = |ty: Option<&str>| {
+        let ty = ty.unwrap_or("");
+        config::expand_type_alias(ty)
+            .ok()
+            .flatten()
+            .unwrap_or(ty)
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .any(|v| v == "datetime" || v == "DateTime")
+    };
+    let cells = match flag {
+        "Template" => (0..sheet.width())
+            .filter(|c| is_datetime(sheet.cell_content(*c, CFG.row_of_type)))
+            .flat_map(|c| (0..sheet.height()).map(move |r| (c, r)))
+            .collect::<Vec<_>>(),
+        // a row per value, the columns are ident, type and value
+        "GlobalConfig" => (0..sheet.height())
+            .filter(|r| is_datetime(sheet.cell_content(1, *r)))
+            .map(|r| (2, r))
+            .collect(),
+        _ => return,
+    };
+    let width = sheet.width();
+    for (c, r) in cells {
+        if let Some(date) = sheet.date_text(c, r) {
+            sheet.cells[r * width + c] = Some(Rc::new(format!("\"{}\"", date)));
+        }
+    }
+}
+
+/// `#N/A`, `#REF!` and the like of broken formulas are read as plain texts
+fn report_error_values(name: &str, flag: &str, sheet: &ExcelTable) {
+    let cells = (0..sheet.height())
+        .flat_map(|y| (0..sheet.width()).map(move |x| (x, y)))
+        .filter_map(|(x, y)| match sheet.cell_value(x, y) {
+            Some(CellValue::Error(e)) => Some(format!("{} {}", sheet.get_cell_name(x, y), e)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if !cells.is_empty() {
+        eprintln!(
+            "{}",
+            Yellow.bold().paint(format!(
+                "[Warning]: the sheet {} of {}.xlsx has error values: {}",
+                flag,
+                name,
+                cells.join(", ")
+            ))
+        );
+    }
+}
+
+/// `duration` is emitted as `System.TimeSpan` and `datetime` as a `long` timestamp
+pub fn replace_time_types(val: &str) -> String {
+    let mut ret = String::with_capacity(val.len());
+    let mut start = None;
+    for (i, c) in val.char_indices().chain([(val.len(), ' ')]) {
+        if c.is_alphanumeric() || c == '_' || c == '.' {
+            start.get_or_insert(i);
+            continue;
+        }
+        if let Some(s) = start.take() {
+            ret.push_str(match &val[s..i] {
+                "duration" | "Duration" => "System.TimeSpan",
+                "datetime" | "DateTime" => "long",
+                v => v,
+            });
+        }
+        if i < val.len() {
+            ret.push(c);
+        }
+    }
+    ret
+}
+
 pub fn split(pat: &str) -> Result<Vec<&str>, Error> {
     let pat_trim = pat.trim();
     let mut ret = Vec::new();
@@ -149,10 +237,26 @@ pub fn split(pat: &str) -> Result<Vec<&str>, Error> {
         let mut brackets = Stack::new();
         let mut begin = 1;
         let mut charidx = 0;
+        // separators inside of a quoted string don't count
+        let mut quoted = false;
+        let mut escaped = false;
 
         for (_, v) in flow.iter().enumerate() {
             charidx += v.len_utf8();
+            if quoted {
+                if escaped {
+                    escaped = false;
+                } else if *v == '\\' {
+                    escaped = true;
+                } else if *v == '"' {
+                    quoted = false;
+                }
+                continue;
+            }
             match v {
+                '"' => {
+                    quoted = true;
+                }
                 '{' => {
                     brackets.push(v);
                 }